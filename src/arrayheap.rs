@@ -0,0 +1,183 @@
+//! A fixed-capacity, allocation-free Min-BinaryHeap using const generics.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/arrayheap.rs
+//!
+//! Same semantics and method names as heap::Heap, but the items are stored
+//! inline in an array, so push() returns Err(item) when it is full, and
+//! pushpop() is the natural bounded insertion.
+//! Only core is used here.
+
+use crate::heap::{heapify_downward, heapify_upward};
+use core::fmt;
+use core::mem::MaybeUninit;
+
+pub struct ArrayHeap<T: PartialOrd, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    len: usize, // data[..len] are initialized
+}
+
+impl<T: PartialOrd, const N: usize> Default for ArrayHeap<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd, const N: usize> ArrayHeap<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            data: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            let last = self.len - 1;
+            self.as_mut_slice().swap(0, last);
+            self.len = last;
+            // SAFETY: data[len] was initialized, and it is out of data[..len] now
+            let ret = unsafe { self.data[self.len].assume_init_read() };
+            heapify_downward(self.as_mut_slice(), 0);
+            Some(ret)
+        }
+    }
+
+    /// return the item back if it is full
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(item);
+        }
+        let i = self.len;
+        self.data[i].write(item);
+        self.len += 1;
+        heapify_upward(self.as_mut_slice(), i);
+        Ok(())
+    }
+
+    /// push then pop
+    pub fn pushpop(&mut self, mut item: T) -> T {
+        if !self.is_empty() && item > self.as_slice()[0] {
+            core::mem::swap(&mut item, &mut self.as_mut_slice()[0]);
+            heapify_downward(self.as_mut_slice(), 0);
+        }
+        item
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    pub fn clear(&mut self) {
+        let len = self.len;
+        // leak rather than double drop, if a drop panics
+        self.len = 0;
+        // SAFETY: data[..len] were initialized
+        unsafe {
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                self.data.as_mut_ptr() as *mut T,
+                len,
+            ));
+        }
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        // SAFETY: data[..len] are initialized
+        unsafe { core::slice::from_raw_parts(self.data.as_ptr() as *const T, self.len) }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: data[..len] are initialized
+        unsafe { core::slice::from_raw_parts_mut(self.data.as_mut_ptr() as *mut T, self.len) }
+    }
+}
+
+impl<T: PartialOrd, const N: usize> Drop for ArrayHeap<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: PartialOrd + fmt::Debug, const N: usize> fmt::Debug for ArrayHeap<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArrayHeap")
+            .field("data", &self.as_slice())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_array_heap() {
+        let mut heap = ArrayHeap::<i32, 5>::new();
+        assert_eq!(None, heap.pop());
+        assert_eq!(None, heap.peek());
+        for i in [4, 9, 7, 3, 1] {
+            heap.push(i).unwrap();
+        }
+        assert!(heap.is_full());
+        assert_eq!(Err(0), heap.push(0));
+        assert_eq!(0, heap.pushpop(0));
+        assert_eq!(1, heap.pushpop(8));
+        assert_eq!(Some(&3), heap.peek());
+        assert_eq!(31, heap.iter().sum::<i32>());
+        let sorted: Vec<i32> = std::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(vec![3, 4, 7, 8, 9], sorted);
+
+        let mut heap = ArrayHeap::<i32, 0>::new();
+        assert_eq!(Err(1), heap.push(1));
+        assert_eq!(1, heap.pushpop(1));
+        assert_eq!(0, heap.capacity());
+    }
+
+    #[test]
+    fn test_array_heap_drop() {
+        let rc = Rc::new(());
+        {
+            let mut heap = ArrayHeap::<_, 8>::new();
+            for i in 0..6 {
+                heap.push((i, Rc::clone(&rc))).unwrap();
+            }
+            assert_eq!(7, Rc::strong_count(&rc));
+            heap.pop();
+            assert_eq!(6, Rc::strong_count(&rc));
+            assert_eq!(
+                "ArrayHeap { data: [(1, ()), (3, ()), (2, ()), (5, ()), (4, ())] }",
+                format!("{heap:?}")
+            );
+        }
+        assert_eq!(1, Rc::strong_count(&rc));
+    }
+}
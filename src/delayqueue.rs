@@ -0,0 +1,210 @@
+//! A delay queue (items become available at an Instant) built on Heap.
+//!
+//! version 0.1.1
+//! https://github.com/wufangjie/utils/blob/main/src/delayqueue.rs
+//!
+//! The clock is injectable (any Fn() -> Instant is a Clock), so tests can
+//! move the time forward instead of sleeping.
+//! Cancelled items are removed lazily from the heap, but the top of the heap
+//! is always a live one, and the heap is compacted once the cancelled ones
+//! outnumber the live ones.
+
+use crate::heap::Heap;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl<F: Fn() -> Instant> Clock for F {
+    fn now(&self) -> Instant {
+        self()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key(u64);
+
+pub struct DelayQueue<T, C: Clock = SystemClock> {
+    heap: Heap<(Instant, Key)>, // ties are broken by insertion order
+    items: HashMap<Key, T>,
+    next_key: u64,
+    clock: C,
+}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DelayQueue<T> {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<T, C: Clock> DelayQueue<T, C> {
+    pub fn with_clock(clock: C) -> Self {
+        DelayQueue {
+            heap: Heap::new(),
+            items: HashMap::new(),
+            next_key: 0,
+            clock,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn insert_at(&mut self, deadline: Instant, item: T) -> Key {
+        let key = Key(self.next_key);
+        self.next_key += 1;
+        self.heap.push((deadline, key));
+        self.items.insert(key, item);
+        key
+    }
+
+    pub fn insert_after(&mut self, delay: Duration, item: T) -> Key {
+        self.insert_at(self.clock.now() + delay, item)
+    }
+
+    /// return None if the item is already expired (polled) or cancelled
+    pub fn cancel(&mut self, key: Key) -> Option<T> {
+        let ret = self.items.remove(&key);
+        if self.heap.len() > 2 * self.items.len() {
+            let items = &self.items;
+            self.heap.retain(|(_, key)| items.contains_key(key));
+        }
+        self.purge();
+        ret
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.heap.peek().map(|(deadline, _)| *deadline)
+    }
+
+    /// all the items whose deadline <= now, the earliest first
+    pub fn poll_expired(&mut self, now: Instant) -> Vec<T> {
+        let mut ret = vec![];
+        while let Some((deadline, key)) = self.heap.peek() {
+            if *deadline > now {
+                break;
+            }
+            let key = *key;
+            self.heap.pop();
+            if let Some(item) = self.items.remove(&key) {
+                ret.push(item);
+            }
+        }
+        self.purge();
+        ret
+    }
+
+    /// poll_expired() with the clock's now
+    pub fn poll(&mut self) -> Vec<T> {
+        let now = self.clock.now();
+        self.poll_expired(now)
+    }
+
+    /// pop the cancelled items on the top
+    fn purge(&mut self) {
+        while let Some((_, key)) = self.heap.peek() {
+            if self.items.contains_key(key) {
+                return;
+            }
+            self.heap.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_delay_queue() {
+        let start = Instant::now();
+        let now = Rc::new(Cell::new(start));
+        let clock = {
+            let now = Rc::clone(&now);
+            move || now.get()
+        };
+        let secs = Duration::from_secs;
+        let mut dq = DelayQueue::with_clock(clock);
+        assert_eq!(None, dq.next_deadline());
+
+        let a = dq.insert_after(secs(5), 'a');
+        dq.insert_after(secs(1), 'b');
+        dq.insert_at(start + secs(3), 'c');
+        let d = dq.insert_after(secs(1), 'd');
+        dq.insert_after(secs(3), 'e');
+        assert_eq!(5, dq.len());
+        assert_eq!(Some(start + secs(1)), dq.next_deadline());
+        assert!(dq.poll().is_empty());
+
+        now.set(start + secs(1));
+        assert_eq!(vec!['b', 'd'], dq.poll());
+        assert_eq!(None, dq.cancel(d));
+        assert_eq!(Some(start + secs(3)), dq.next_deadline());
+
+        assert_eq!(Some('a'), dq.cancel(a));
+        assert_eq!(vec!['c', 'e'], dq.poll_expired(start + secs(4)));
+        assert_eq!(None, dq.next_deadline());
+        assert!(dq.is_empty());
+
+        // cancel the top
+        let f = dq.insert_after(secs(2), 'f');
+        dq.insert_after(secs(7), 'g');
+        assert_eq!(Some('f'), dq.cancel(f));
+        assert_eq!(Some(start + secs(8)), dq.next_deadline());
+        assert!(dq.poll_expired(start + secs(7)).is_empty());
+        now.set(start + secs(10));
+        assert_eq!(vec!['g'], dq.poll());
+    }
+
+    #[test]
+    fn test_delay_queue_compact() {
+        // ttl style: cancel then re-insert on every access
+        let start = Instant::now();
+        let mut dq = DelayQueue::with_clock(move || start);
+        dq.insert_at(start, 0); // keep the top live
+        let mut key = dq.insert_at(start + Duration::from_secs(60), 1);
+        for i in 0..1000 {
+            dq.cancel(key);
+            key = dq.insert_at(start + Duration::from_secs(61 + i), 1);
+            assert_eq!(2, dq.len());
+            assert!(dq.heap.len() <= 2 * dq.len() + 1);
+        }
+        assert_eq!(vec![0], dq.poll());
+        assert_eq!(Some(start + Duration::from_secs(1060)), dq.next_deadline());
+    }
+
+    #[test]
+    fn test_delay_queue_system_clock() {
+        let mut dq = DelayQueue::new();
+        dq.insert_after(Duration::ZERO, 1);
+        dq.insert_after(Duration::from_secs(3600), 2);
+        assert_eq!(vec![1], dq.poll());
+        assert_eq!(1, dq.len());
+    }
+}
@@ -0,0 +1,261 @@
+//! External merge sort, spilling sorted runs to disk and merging them by Heap.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/externalsort.rs
+//!
+//! external_sort(iter, chunk_size, tmp_dir) sorts every chunk_size items in
+//! memory, writes them into a run file, then lazily k-way merges the runs.
+//! Run file format: for every item, a u32 (little endian) length followed by
+//! the bytes produced by Codec::encode.
+//! The sort is stable, and the run files are removed when the iterator drops.
+
+use crate::heap::Heap;
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+pub trait Codec: Sized {
+    fn encode(&self, buf: &mut Vec<u8>);
+    fn decode(buf: &[u8]) -> io::Result<Self>;
+}
+
+macro_rules! impl_codec_for_num {
+    ($($t:ty),*) => {
+        $(
+            impl Codec for $t {
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(buf: &[u8]) -> io::Result<Self> {
+                    buf.try_into()
+                        .map(<$t>::from_le_bytes)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_for_num!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl Codec for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(buf: &[u8]) -> io::Result<Self> {
+        String::from_utf8(buf.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Codec for Vec<u8> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+
+    fn decode(buf: &[u8]) -> io::Result<Self> {
+        Ok(buf.to_vec())
+    }
+}
+
+struct Run<T> {
+    reader: BufReader<File>,
+    buf: Vec<u8>,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Codec> Run<T> {
+    fn write(path: &Path, chunk: &[T]) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let mut buf = vec![];
+        for item in chunk {
+            buf.clear();
+            item.encode(&mut buf);
+            let len = u32::try_from(buf.len())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            writer.write_all(&len.to_le_bytes())?;
+            writer.write_all(&buf)?;
+        }
+        writer.flush()
+    }
+
+    fn open(path: &Path) -> io::Result<Self> {
+        Ok(Run {
+            reader: BufReader::new(File::open(path)?),
+            buf: vec![],
+            _marker: std::marker::PhantomData,
+        })
+    }
+
+    fn read_next(&mut self) -> io::Result<Option<T>> {
+        if self.reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut len = [0u8; 4];
+        self.reader.read_exact(&mut len)?;
+        self.buf.resize(u32::from_le_bytes(len) as usize, 0);
+        self.reader.read_exact(&mut self.buf)?;
+        T::decode(&self.buf).map(Some)
+    }
+}
+
+pub struct ExternalSort<T: Codec + PartialOrd> {
+    heap: Heap<(T, usize)>, // run index breaks ties, which keeps the sort stable
+    runs: Vec<Run<T>>,
+    paths: Vec<PathBuf>,
+    err: Option<io::Error>,
+}
+
+pub fn external_sort<T, I, P>(iter: I, chunk_size: usize, tmp_dir: P) -> io::Result<ExternalSort<T>>
+where
+    T: Codec + PartialOrd,
+    I: IntoIterator<Item = T>,
+    P: AsRef<Path>,
+{
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let chunk_size = chunk_size.max(1);
+    let mut res = ExternalSort {
+        heap: Heap::new(),
+        runs: vec![],
+        paths: vec![],
+        err: None,
+    };
+    let mut iter = iter.into_iter();
+    loop {
+        let mut chunk: Vec<T> = iter.by_ref().take(chunk_size).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        chunk.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let path = tmp_dir.as_ref().join(format!(
+            "external-sort-{}-{}.run",
+            std::process::id(),
+            COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        res.paths.push(path.clone()); // so that it will be removed on error
+        Run::write(&path, &chunk)?;
+    }
+    for path in &res.paths {
+        res.runs.push(Run::open(path)?);
+    }
+    for idx in 0..res.runs.len() {
+        res.refill(idx)?;
+    }
+    Ok(res)
+}
+
+impl<T: Codec + PartialOrd> ExternalSort<T> {
+    fn refill(&mut self, idx: usize) -> io::Result<()> {
+        if let Some(item) = self.runs[idx].read_next()? {
+            self.heap.push((item, idx));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Codec + PartialOrd> Iterator for ExternalSort<T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.err.take() {
+            self.heap.clear(); // stop after an error
+            return Some(Err(e));
+        }
+        let (item, idx) = self.heap.pop()?;
+        if let Err(e) = self.refill(idx) {
+            self.err = Some(e);
+        }
+        Some(Ok(item))
+    }
+}
+
+impl<T: Codec + PartialOrd> Drop for ExternalSort<T> {
+    fn drop(&mut self) {
+        self.runs.clear(); // close the files first
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("utils-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_external_sort() {
+        let dir = temp_dir("external-sort");
+        let mut seed = 36u64;
+        let data: Vec<u64> = (0..10000)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                seed >> 40
+            })
+            .collect();
+        let mut expected = data.clone();
+        expected.sort();
+
+        let sorted = external_sort(data, 777, &dir).unwrap();
+        assert_eq!(13, fs::read_dir(&dir).unwrap().count());
+        let sorted: Vec<u64> = sorted.map(|x| x.unwrap()).collect();
+        assert_eq!(expected, sorted);
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+
+        let words = "the quick brown fox jumps over the lazy dog".split(' ');
+        let sorted: io::Result<Vec<String>> = external_sort(words.map(String::from), 2, &dir)
+            .unwrap()
+            .collect();
+        assert_eq!(
+            "brown dog fox jumps lazy over quick the the",
+            sorted.unwrap().join(" ")
+        );
+
+        assert_eq!(
+            0,
+            external_sort(Vec::<i32>::new(), 0, &dir).unwrap().count()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[derive(Debug, PartialEq, PartialOrd)]
+    struct NotThirteen(u8);
+
+    impl Codec for NotThirteen {
+        fn encode(&self, buf: &mut Vec<u8>) {
+            buf.push(self.0);
+        }
+
+        fn decode(buf: &[u8]) -> io::Result<Self> {
+            match buf {
+                [13] => Err(io::Error::new(io::ErrorKind::InvalidData, "13")),
+                [x] => Ok(NotThirteen(*x)),
+                _ => Err(io::Error::new(io::ErrorKind::InvalidData, "length")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_external_sort_error() {
+        let dir = temp_dir("external-sort-error");
+        let data = vec![NotThirteen(13), NotThirteen(1), NotThirteen(2)];
+        let mut sorted = external_sort(data, 2, &dir).unwrap();
+        assert_eq!(1, sorted.next().unwrap().unwrap().0);
+        assert!(sorted.next().unwrap().is_err());
+        assert!(sorted.next().is_none());
+        drop(sorted);
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+
+        assert!(external_sort(vec![1], 1, dir.join("not-exists")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
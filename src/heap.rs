@@ -1,10 +1,13 @@
 //! A Min-BinaryHeap implementation.
 //!
-//! version 0.1.7
+//! version 0.2.1
 //! https://github.com/wufangjie/utils/blob/main/src/heap.rs
 //!
 //! NOTE: std::collections::BinaryHeap is a max heap,
 //! as fast as this min heap implemention.
+//!
+//! heapify, heappush, heappop, heappushpop, heapreplace, nsmallest, nlargest
+//! are Python's heapq style functions, for a caller-owned Vec (or slice).
 
 #[derive(Debug)]
 pub struct Heap<T: PartialOrd> {
@@ -58,7 +61,7 @@ where
         self.heapify_upward(self.len() - 1);
     }
 
-    /// push then pop, TODO: do we need poppush?
+    /// push then pop
     pub fn pushpop(&mut self, mut item: T) -> T {
         if !self.is_empty() && item > self.data[0] {
             std::mem::swap(&mut item, &mut self.data[0]);
@@ -67,8 +70,45 @@ where
         item
     }
 
+    /// pop then push (a.k.a. poppush), return None and just push if empty
+    pub fn replace(&mut self, mut item: T) -> Option<T> {
+        if self.is_empty() {
+            self.data.push(item);
+            None
+        } else {
+            std::mem::swap(&mut item, &mut self.data[0]);
+            self.heapify_downward(0);
+            Some(item)
+        }
+    }
+
+    /// remove the item at index i of the underlying data (the order of iter())
+    pub fn remove_at(&mut self, i: usize) -> Option<T> {
+        if i >= self.len() {
+            return None;
+        }
+        let ret = self.data.swap_remove(i);
+        if i < self.len() {
+            if i > 0 && self.data[i] < self.data[(i - 1) >> 1] {
+                self.heapify_upward(i);
+            } else {
+                self.heapify_downward(i);
+            }
+        }
+        Some(ret)
+    }
+
+    /// remove the first item (the order of iter()) satisfied pred
+    pub fn remove_where<F>(&mut self, pred: F) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let i = self.data.iter().position(pred)?;
+        self.remove_at(i)
+    }
+
     pub fn peek(&self) -> Option<&T> {
-        self.data.get(0)
+        self.data.first()
     }
 
     // pub fn peek_mut(&mut self) -> Option<&mut T> {
@@ -76,39 +116,88 @@ where
     //     self.data.get_mut(0)
     // }
 
-    fn heapify_downward(&mut self, mut i: usize) {
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
         let n = self.len();
-        loop {
-            let j = (i + 1) << 1;
-            if j < n && self.data[i] > self.data[j] {
-                if self.data[j - 1] < self.data[j] {
-                    self.data.swap(i, j - 1);
-                    i = j - 1;
-                } else {
-                    self.data.swap(i, j);
-                    i = j;
-                }
-            } else if j - 1 < n && self.data[i] > self.data[j - 1] {
-                self.data.swap(i, j - 1);
-                i = j - 1;
-            } else {
-                return;
-            }
+        self.data.retain(f);
+        if self.len() < n {
+            self.rebuild();
         }
     }
 
-    fn heapify_upward(&mut self, mut i: usize) {
-        while i > 0 {
-            let j = (i - 1) >> 1;
-            if self.data[i] < self.data[j] {
-                self.data.swap(i, j);
-                i = j;
-            } else {
-                return;
+    pub fn append(&mut self, other: &mut Self) {
+        if self.len() < other.len() {
+            std::mem::swap(self, other);
+        }
+        let start = self.len();
+        self.data.append(&mut other.data);
+        self.rebuild_tail(start);
+    }
+
+    /// lazily pop items in ascending order, the rest are dropped with the iterator
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T> {
+        DrainSorted { heap: self }
+    }
+
+    /// in-place heapsort, ascending
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut n = self.len();
+        while n > 1 {
+            n -= 1;
+            self.data.swap(0, n);
+            self.heapify_downward_within(0, n);
+        }
+        self.data.reverse();
+        self.data
+    }
+
+    #[inline]
+    fn rebuild(&mut self) {
+        heapify(&mut self.data);
+    }
+
+    /// restore the heap after pushing data[start..] directly into data,
+    /// sift them up one by one or rebuild the whole, whichever is cheaper
+    fn rebuild_tail(&mut self, start: usize) {
+        let n = self.len();
+        let added = n - start;
+        // log2(start) + 1, as rough as std's BinaryHeap
+        let log2 = (usize::BITS - start.leading_zeros()) as usize;
+        if start < added || 2 * n < added * log2 {
+            self.rebuild();
+        } else {
+            for i in start..n {
+                self.heapify_upward(i);
             }
         }
     }
 
+    #[inline]
+    fn heapify_downward(&mut self, i: usize) {
+        heapify_downward(&mut self.data, i);
+    }
+
+    /// only data[..n] is treated as the heap
+    #[inline]
+    fn heapify_downward_within(&mut self, i: usize, n: usize) {
+        heapify_downward(&mut self.data[..n], i);
+    }
+
+    #[inline]
+    fn heapify_upward(&mut self, i: usize) {
+        heapify_upward(&mut self.data, i);
+    }
+
     // fn heapify_downward(&mut self, i: usize) {
     //     let j = (i + 1) << 1;
     //     if j < self.len() && self.data[i] > self.data[j] {
@@ -136,20 +225,151 @@ where
     // }
 
     pub fn into_inner(self) -> Vec<T> {
-	self.data
+        self.data
+    }
+}
+
+/// the sift logic on a slice, shared by the other heaps in this crate
+pub(crate) fn heapify_downward<T: PartialOrd>(data: &mut [T], mut i: usize) {
+    let n = data.len();
+    loop {
+        let j = (i + 1) << 1;
+        if j < n && data[i] > data[j] {
+            if data[j - 1] < data[j] {
+                data.swap(i, j - 1);
+                i = j - 1;
+            } else {
+                data.swap(i, j);
+                i = j;
+            }
+        } else if j - 1 < n && data[i] > data[j - 1] {
+            data.swap(i, j - 1);
+            i = j - 1;
+        } else {
+            return;
+        }
+    }
+}
+
+pub(crate) fn heapify_upward<T: PartialOrd>(data: &mut [T], mut i: usize) {
+    while i > 0 {
+        let j = (i - 1) >> 1;
+        if data[i] < data[j] {
+            data.swap(i, j);
+            i = j;
+        } else {
+            return;
+        }
+    }
+}
+
+pub fn heapify<T: PartialOrd>(data: &mut [T]) {
+    for i in (0..data.len() >> 1).rev() {
+        heapify_downward(data, i);
+    }
+}
+
+pub fn heappush<T: PartialOrd>(heap: &mut Vec<T>, item: T) {
+    let i = heap.len();
+    heap.push(item);
+    heapify_upward(heap, i);
+}
+
+pub fn heappop<T: PartialOrd>(heap: &mut Vec<T>) -> Option<T> {
+    if heap.is_empty() {
+        None
+    } else {
+        let ret = heap.swap_remove(0);
+        heapify_downward(heap, 0);
+        Some(ret)
+    }
+}
+
+/// push then pop
+pub fn heappushpop<T: PartialOrd>(heap: &mut [T], mut item: T) -> T {
+    if !heap.is_empty() && item > heap[0] {
+        std::mem::swap(&mut item, &mut heap[0]);
+        heapify_downward(heap, 0);
     }
+    item
+}
+
+/// pop then push, return None and just push if empty
+pub fn heapreplace<T: PartialOrd>(heap: &mut Vec<T>, mut item: T) -> Option<T> {
+    if heap.is_empty() {
+        heap.push(item);
+        None
+    } else {
+        std::mem::swap(&mut item, &mut heap[0]);
+        heapify_downward(heap, 0);
+        Some(item)
+    }
+}
+
+/// the n smallest items, in ascending order
+pub fn nsmallest<T, I>(n: usize, iter: I) -> Vec<T>
+where
+    T: PartialOrd,
+    I: IntoIterator<Item = T>,
+{
+    crate::topk::TopKExt::bottom_k(iter.into_iter(), n).collect()
+}
+
+/// the n largest items, in descending order
+pub fn nlargest<T, I>(n: usize, iter: I) -> Vec<T>
+where
+    T: PartialOrd,
+    I: IntoIterator<Item = T>,
+{
+    crate::topk::TopKExt::top_k(iter.into_iter(), n).collect()
 }
 
 impl<T: PartialOrd> From<Vec<T>> for Heap<T> {
     fn from(data: Vec<T>) -> Self {
         let mut res = Self { data };
-        for i in (0..res.len() >> 1).rev() {
-            res.heapify_downward(i);
-        }
+        res.rebuild();
         res
     }
 }
 
+impl<T: PartialOrd> FromIterator<T> for Heap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T: PartialOrd> Extend<T> for Heap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let start = self.len();
+        self.data.extend(iter);
+        self.rebuild_tail(start);
+    }
+}
+
+pub struct DrainSorted<'a, T: PartialOrd> {
+    heap: &'a mut Heap<T>,
+}
+
+impl<T: PartialOrd> Iterator for DrainSorted<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.heap.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T: PartialOrd> ExactSizeIterator for DrainSorted<'_, T> {}
+
+impl<T: PartialOrd> Drop for DrainSorted<'_, T> {
+    fn drop(&mut self) {
+        self.heap.clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,4 +419,144 @@ mod tests {
         }
         is_a_min_heap(&heap);
     }
+
+    #[test]
+    fn test_heapq() {
+        let mut data = vec![4, 9, 7, 3, 1, 8, 6, 0, 5, 2, 0];
+        heapify(&mut data);
+        let mut heap = Heap::from(data.clone());
+        is_a_min_heap(&heap);
+        assert_eq!(heap.into_inner(), data); // the same sift logic
+
+        heappush(&mut data, -1);
+        heap = Heap::from(data.clone());
+        is_a_min_heap(&heap);
+        assert_eq!(Some(-1), heappop(&mut data));
+        assert_eq!(-2, heappushpop(&mut data, -2));
+        assert_eq!(0, heappushpop(&mut data, 10));
+        assert_eq!(Some(0), heapreplace(&mut data, 11));
+        heap = Heap::from(data.clone());
+        is_a_min_heap(&heap);
+        assert_eq!(
+            heap.into_sorted_vec(),
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]
+        );
+
+        let mut empty = vec![];
+        assert_eq!(None, heappop(&mut empty));
+        assert_eq!(1, heappushpop(&mut empty, 1));
+        assert_eq!(None, heapreplace(&mut empty, 2));
+        assert_eq!(vec![2], empty);
+
+        let data = [4, 9, 7, 3, 1, 8, 6, 0, 5, 2, 0];
+        assert_eq!(vec![0, 0, 1], nsmallest(3, data));
+        assert_eq!(vec![9, 8, 7], nlargest(3, data.iter().copied()));
+        assert_eq!(11, nlargest(20, data).len());
+        assert!(nsmallest(0, data).is_empty());
+    }
+
+    #[test]
+    fn test_heap_bulk() {
+        let heap = Heap::from(vec![4, 9, 7, 3, 1, 8, 6, 0, 5, 2, 0]);
+        assert_eq!(heap.iter().sum::<i32>(), 45);
+        assert_eq!(
+            heap.into_sorted_vec(),
+            vec![0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+        );
+        assert!(Heap::<i32>::new().into_sorted_vec().is_empty());
+
+        let mut heap: Heap<i32> = (0..20).rev().collect();
+        is_a_min_heap(&heap);
+        heap.retain(|x| x % 3 != 0);
+        is_a_min_heap(&heap);
+        assert_eq!(13, heap.len());
+
+        heap.extend([-5, 100]); // sift up one by one
+        is_a_min_heap(&heap);
+        heap.extend((30..60).rev()); // rebuild
+        is_a_min_heap(&heap);
+        assert_eq!(45, heap.len());
+
+        let mut other = Heap::from(vec![-1, 18, 55]);
+        other.append(&mut heap);
+        assert!(heap.is_empty());
+        is_a_min_heap(&other);
+        assert_eq!(48, other.len());
+
+        let drained: Vec<i32> = other.drain_sorted().take(3).collect();
+        assert_eq!(drained, vec![-5, -1, 1]);
+        assert!(other.is_empty());
+
+        let mut heap = Heap::from(vec![3, 1, 2]);
+        let mut iter = heap.drain_sorted();
+        assert_eq!(3, iter.len());
+        assert_eq!(Some(1), iter.next());
+        assert_eq!(2, iter.len());
+        drop(iter);
+        heap.push(5);
+        heap.clear();
+        assert_eq!(None, heap.peek());
+    }
+
+    #[test]
+    fn test_heap_extend_rebuild() {
+        use std::cell::Cell;
+
+        struct Counted<'a>(i32, &'a Cell<usize>);
+        impl PartialEq for Counted<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl PartialOrd for Counted<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                self.1.set(self.1.get() + 1);
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        // a descending tail is the worst case of sifting up one by one
+        let n = 1 << 16;
+        for init in [0, 1] {
+            let count = Cell::new(0);
+            let mut heap = Heap::new();
+            heap.extend((0..init).map(|x| Counted(n + x, &count)));
+            heap.extend((0..n).rev().map(|x| Counted(x, &count)));
+            assert!(count.get() < 3 * n as usize, "{}", count.get());
+            let sorted: Vec<i32> = std::iter::from_fn(|| heap.pop().map(|x| x.0))
+                .take(3)
+                .collect();
+            assert_eq!(vec![0, 1, 2], sorted);
+        }
+    }
+
+    #[test]
+    fn test_heap_replace_remove() {
+        let mut heap = Heap::new();
+        assert_eq!(None, heap.replace(5));
+        assert_eq!(Some(5), heap.replace(7));
+        heap.extend([4, 9, 3, 1, 8, 6, 0, 2]);
+        assert_eq!(Some(0), heap.replace(10));
+        is_a_min_heap(&heap);
+        assert_eq!(Some(1), heap.replace(-1));
+        is_a_min_heap(&heap);
+
+        assert_eq!(None, heap.remove_at(heap.len()));
+        assert_eq!(Some(-1), heap.remove_at(0));
+        assert_eq!(Some(7), heap.remove_where(|&x| x == 7));
+        assert_eq!(None, heap.remove_where(|&x| x == 7));
+        is_a_min_heap(&heap);
+
+        // every position, including the ones need sift up
+        let base = vec![0, 10, 1, 11, 12, 2, 3, 13, 14, 15, 16, 4, 5, 6, 7];
+        for i in 0..base.len() {
+            let mut heap = Heap::from(base.clone());
+            let x = heap.remove_at(i).unwrap();
+            is_a_min_heap(&heap);
+            let mut expected = base.clone();
+            expected.retain(|&y| y != x);
+            expected.sort();
+            assert_eq!(expected, heap.into_sorted_vec());
+        }
+    }
 }
@@ -0,0 +1,286 @@
+//! Huffman coding for byte slices, driven by Heap.
+//!
+//! version 0.1.1
+//! https://github.com/wufangjie/utils/blob/main/src/huffman.rs
+//!
+//! The code is canonical, so the header only needs the code lengths:
+//! | original length (u64 LE) | number of symbols - 1 (u8) | (symbol, length) * n | bits |
+//! The header stops after the original length if the input is empty,
+//! and the bits are written most significant bit first.
+//! Codes are kept in u64, a code longer than 64 bits needs an input larger
+//! than Fibonacci(66) (about 2.7e13) bytes, so it is not handled.
+
+use crate::heap::Heap;
+
+#[derive(Debug, Clone)]
+pub struct CodeTable {
+    codes: [(u64, u8); 256], // (code, length), length 0 means absent
+}
+
+impl CodeTable {
+    /// build the canonical Huffman code of the symbols with a non-zero frequency
+    pub fn from_freqs(freqs: &[u64; 256]) -> Self {
+        let mut lens = [0u8; 256];
+        // (freq, id), id < 256 for leaves, the rest are internal nodes
+        let mut heap: Heap<(u64, usize)> = (0..256)
+            .filter(|&s| freqs[s] > 0)
+            .map(|s| (freqs[s], s))
+            .collect();
+        if heap.len() == 1 {
+            lens[heap.pop().unwrap().1] = 1;
+        } else {
+            let mut parent = vec![]; // parent[id] for every node
+            parent.resize(256, usize::MAX);
+            while heap.len() > 1 {
+                let (f1, a) = heap.pop().unwrap();
+                let (f2, b) = heap.pop().unwrap();
+                let id = parent.len();
+                parent.push(usize::MAX);
+                parent[a] = id;
+                parent[b] = id;
+                heap.push((f1 + f2, id));
+            }
+            for s in 0..256 {
+                let mut p = parent[s];
+                while p != usize::MAX {
+                    lens[s] += 1;
+                    p = parent[p];
+                }
+            }
+        }
+        Self::from_lens(&lens)
+    }
+
+    /// the lengths should satisfy the Kraft inequality (see is_prefix_free())
+    fn from_lens(lens: &[u8; 256]) -> Self {
+        let mut codes = [(0u64, 0u8); 256];
+        let mut code = 0u128; // a length can be 64, so u64 may overflow
+        let mut pre = 0u8;
+        for s in Self::canonical_order(lens) {
+            code <<= lens[s] - pre;
+            codes[s] = (code as u64, lens[s]);
+            code += 1;
+            pre = lens[s];
+        }
+        CodeTable { codes }
+    }
+
+    /// Kraft inequality: sum(2^-len) <= 1, lengths should be in 1..=64
+    fn is_prefix_free(lens: &[u8; 256]) -> bool {
+        let sum: u128 = lens
+            .iter()
+            .filter(|&&len| len > 0)
+            .map(|&len| 1u128 << (64 - len))
+            .sum();
+        sum <= 1 << 64
+    }
+
+    /// the present symbols, sorted by (length, symbol)
+    fn canonical_order(lens: &[u8; 256]) -> Vec<usize> {
+        let mut syms: Vec<usize> = (0..256).filter(|&s| lens[s] > 0).collect();
+        syms.sort_by_key(|&s| (lens[s], s));
+        syms
+    }
+
+    /// (code, length) of the symbol
+    pub fn get(&self, symbol: u8) -> Option<(u64, u8)> {
+        let (code, len) = self.codes[symbol as usize];
+        (len > 0).then_some((code, len))
+    }
+}
+
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut ret = (data.len() as u64).to_le_bytes().to_vec();
+    if data.is_empty() {
+        return ret;
+    }
+    let mut freqs = [0u64; 256];
+    for &b in data {
+        freqs[b as usize] += 1;
+    }
+    let table = CodeTable::from_freqs(&freqs);
+    let present: Vec<u8> = (0..=255).filter(|&s| freqs[s as usize] > 0).collect();
+    ret.push((present.len() - 1) as u8);
+    for s in present {
+        ret.push(s);
+        ret.push(table.codes[s as usize].1);
+    }
+
+    let mut acc = 0u8; // bits not written yet
+    let mut nbit = 0;
+    for &b in data {
+        let (code, len) = table.codes[b as usize];
+        for i in (0..len).rev() {
+            acc = (acc << 1) | ((code >> i) & 1) as u8;
+            nbit += 1;
+            if nbit == 8 {
+                ret.push(acc);
+                acc = 0;
+                nbit = 0;
+            }
+        }
+    }
+    if nbit > 0 {
+        ret.push(acc << (8 - nbit));
+    }
+    ret
+}
+
+/// None if the data is corrupted
+pub fn decode(data: &[u8]) -> Option<Vec<u8>> {
+    let n = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+    if n == 0 {
+        return Some(vec![]);
+    }
+    let nsym = *data.get(8)? as usize + 1;
+    let header = data.get(9..9 + 2 * nsym)?;
+    let bits = &data[9 + 2 * nsym..];
+    if n > bits.len() * 8 {
+        return None; // every symbol takes at least one bit
+    }
+    let mut lens = [0u8; 256];
+    for pair in header.chunks(2) {
+        if pair[1] == 0 || pair[1] > 64 {
+            return None;
+        }
+        lens[pair[0] as usize] = pair[1];
+    }
+    if !CodeTable::is_prefix_free(&lens) {
+        return None;
+    }
+
+    // canonical decoding: codes of the same length are consecutive
+    let syms = CodeTable::canonical_order(&lens);
+    let table = CodeTable::from_lens(&lens);
+    let max_len = lens.iter().copied().max()? as usize;
+    let mut first = vec![u64::MAX; max_len + 1]; // the first code of every length
+    let mut offset = vec![0usize; max_len + 1]; // its index in syms
+    let mut count = vec![0u64; max_len + 1];
+    for (i, &s) in syms.iter().enumerate() {
+        let (code, len) = table.codes[s];
+        let len = len as usize;
+        if count[len] == 0 {
+            first[len] = code;
+            offset[len] = i;
+        }
+        count[len] += 1;
+    }
+
+    let mut ret = Vec::with_capacity(n);
+    let mut code = 0u64;
+    let mut len = 0;
+    'outer: for &byte in bits {
+        for i in (0..8).rev() {
+            code = (code << 1) | ((byte >> i) & 1) as u64;
+            len += 1;
+            if count[len] > 0 && code >= first[len] && code - first[len] < count[len] {
+                ret.push(syms[offset[len] + (code - first[len]) as usize] as u8);
+                if ret.len() == n {
+                    break 'outer;
+                }
+                code = 0;
+                len = 0;
+            } else if len == max_len {
+                return None;
+            }
+        }
+    }
+    (ret.len() == n).then_some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_table() {
+        let mut freqs = [0u64; 256];
+        for (s, f) in [
+            (b'a', 45),
+            (b'b', 13),
+            (b'c', 12),
+            (b'd', 16),
+            (b'e', 9),
+            (b'f', 5),
+        ] {
+            freqs[s as usize] = f;
+        }
+        let table = CodeTable::from_freqs(&freqs);
+        assert_eq!(Some((0b0, 1)), table.get(b'a'));
+        assert_eq!(Some((0b100, 3)), table.get(b'b'));
+        assert_eq!(Some((0b101, 3)), table.get(b'c'));
+        assert_eq!(Some((0b110, 3)), table.get(b'd'));
+        assert_eq!(Some((0b1110, 4)), table.get(b'e'));
+        assert_eq!(Some((0b1111, 4)), table.get(b'f'));
+        assert_eq!(None, table.get(b'g'));
+    }
+
+    #[test]
+    fn test_huffman() {
+        for data in [
+            &b""[..],
+            b"a",
+            b"aaaa",
+            b"abracadabra",
+            &[0, 255, 0, 255, 7],
+        ] {
+            let encoded = encode(data);
+            assert_eq!(Some(data.to_vec()), decode(&encoded));
+        }
+        let text = "the quick brown fox jumps over the lazy dog ".repeat(20);
+        let encoded = encode(text.as_bytes());
+        assert!(encoded.len() < text.len() * 2 / 3);
+        assert_eq!(Some(text.into_bytes()), decode(&encoded));
+
+        let encoded = encode(b"abracadabra");
+        assert_eq!(None, decode(&encoded[..encoded.len() - 1]));
+        assert_eq!(None, decode(&encoded[..5]));
+        assert_eq!(None, decode(&[]));
+
+        // hand made headers, one symbol in the bits
+        let header = |pairs: &[(u8, u8)], bits: &[u8]| {
+            let mut v = vec![1, 0, 0, 0, 0, 0, 0, 0, (pairs.len() - 1) as u8];
+            for &(s, len) in pairs {
+                v.extend([s, len]);
+            }
+            v.extend(bits);
+            v
+        };
+        let bits = [0x80, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(Some(vec![1]), decode(&header(&[(0, 1), (1, 64)], &bits)));
+        assert_eq!(None, decode(&header(&[(0, 1), (1, 64)], &bits[..7])));
+        assert_eq!(None, decode(&header(&[(0, 0)], &bits)));
+        assert_eq!(None, decode(&header(&[(0, 65)], &bits)));
+        let mut huge = header(&[(0, 1), (1, 1)], &bits);
+        huge[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(None, decode(&huge));
+        huge[..8].copy_from_slice(&65u64.to_le_bytes());
+        assert_eq!(None, decode(&huge));
+        huge[..8].copy_from_slice(&64u64.to_le_bytes());
+        assert_eq!(Some([vec![1], vec![0; 63]].concat()), decode(&huge));
+        // not prefix free
+        assert_eq!(None, decode(&header(&[(0, 1), (1, 1), (2, 1)], &bits)));
+        assert_eq!(
+            None,
+            decode(&header(&[(0, 1), (1, 2), (2, 2), (3, 64)], &bits))
+        );
+        assert_eq!(None, decode(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 64, 0]));
+    }
+
+    #[test]
+    fn test_huffman_random() {
+        let mut rng = crate::rng::Rng::new(39);
+        let mut rand = move || rng.next_u64() as usize;
+        for _ in 0..50 {
+            let n = rand() % 5000;
+            // skewed distribution over a random number of symbols
+            let nsym = 1 + rand() % 256;
+            let data: Vec<u8> = (0..n)
+                .map(|_| (rand() % nsym).min(rand() % nsym) as u8)
+                .collect();
+            assert_eq!(Some(data.clone()), decode(&encode(&data)));
+        }
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(Some(data.clone()), decode(&encode(&data)));
+    }
+}
@@ -0,0 +1,220 @@
+//! K-way merge of sorted iterators using Heap.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/kmerge.rs
+//!
+//! kmerge(iters) (merge iterators sorted in ascending order)
+//! kmerge_by(iters, cmp) (merge iterators sorted by cmp)
+//! kmerge_dedup(iters), kmerge_by_dedup(iters, cmp) (also skip equal items)
+//!
+//! Equal items are yielded in the order of the iterators they come from.
+
+use crate::heap::Heap;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+pub type KMerge<I> = KMergeBy<I, fn(&<I as Iterator>::Item, &<I as Iterator>::Item) -> Ordering>;
+
+pub struct KMergeBy<I, F>
+where
+    I: Iterator,
+    F: Fn(&I::Item, &I::Item) -> Ordering,
+{
+    heap: Heap<MergeEntry<I::Item, F>>,
+    iters: Vec<I>,
+    cmp: Rc<F>,
+    dedup: bool,
+}
+
+struct MergeEntry<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    item: T,
+    idx: usize, // which iterator the item comes from
+    cmp: Rc<F>,
+}
+
+impl<T, F> PartialEq for MergeEntry<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<T, F> PartialOrd for MergeEntry<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some((self.cmp)(&self.item, &other.item).then(self.idx.cmp(&other.idx)))
+    }
+}
+
+fn partial_cmp_or_equal<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+pub fn kmerge<II>(iters: II) -> KMerge<<II::Item as IntoIterator>::IntoIter>
+where
+    II: IntoIterator,
+    II::Item: IntoIterator,
+    <II::Item as IntoIterator>::Item: PartialOrd,
+{
+    KMergeBy::new(iters, partial_cmp_or_equal as fn(&_, &_) -> Ordering, false)
+}
+
+pub fn kmerge_dedup<II>(iters: II) -> KMerge<<II::Item as IntoIterator>::IntoIter>
+where
+    II: IntoIterator,
+    II::Item: IntoIterator,
+    <II::Item as IntoIterator>::Item: PartialOrd,
+{
+    KMergeBy::new(iters, partial_cmp_or_equal as fn(&_, &_) -> Ordering, true)
+}
+
+pub fn kmerge_by<II, F>(iters: II, cmp: F) -> KMergeBy<<II::Item as IntoIterator>::IntoIter, F>
+where
+    II: IntoIterator,
+    II::Item: IntoIterator,
+    F: Fn(&<II::Item as IntoIterator>::Item, &<II::Item as IntoIterator>::Item) -> Ordering,
+{
+    KMergeBy::new(iters, cmp, false)
+}
+
+pub fn kmerge_by_dedup<II, F>(
+    iters: II,
+    cmp: F,
+) -> KMergeBy<<II::Item as IntoIterator>::IntoIter, F>
+where
+    II: IntoIterator,
+    II::Item: IntoIterator,
+    F: Fn(&<II::Item as IntoIterator>::Item, &<II::Item as IntoIterator>::Item) -> Ordering,
+{
+    KMergeBy::new(iters, cmp, true)
+}
+
+impl<I, F> KMergeBy<I, F>
+where
+    I: Iterator,
+    F: Fn(&I::Item, &I::Item) -> Ordering,
+{
+    fn new<II>(iters: II, cmp: F, dedup: bool) -> Self
+    where
+        II: IntoIterator,
+        II::Item: IntoIterator<IntoIter = I>,
+    {
+        let iters: Vec<I> = iters.into_iter().map(|it| it.into_iter()).collect();
+        let mut res = KMergeBy {
+            heap: Heap::with_capacity(iters.len()),
+            iters,
+            cmp: Rc::new(cmp),
+            dedup,
+        };
+        for idx in 0..res.iters.len() {
+            res.refill(idx);
+        }
+        res
+    }
+
+    fn refill(&mut self, idx: usize) {
+        if let Some(item) = self.iters[idx].next() {
+            self.heap.push(MergeEntry {
+                item,
+                idx,
+                cmp: Rc::clone(&self.cmp),
+            });
+        }
+    }
+}
+
+impl<I, F> Iterator for KMergeBy<I, F>
+where
+    I: Iterator,
+    F: Fn(&I::Item, &I::Item) -> Ordering,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.heap.pop()?;
+        self.refill(entry.idx);
+        if self.dedup {
+            while let Some(top) = self.heap.peek() {
+                if (self.cmp)(&top.item, &entry.item) != Ordering::Equal {
+                    break;
+                }
+                let idx = top.idx;
+                self.heap.pop();
+                self.refill(idx);
+            }
+        }
+        Some(entry.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let mut lo = self.heap.len();
+        let mut hi = Some(lo);
+        for iter in &self.iters {
+            let (l, h) = iter.size_hint();
+            lo = lo.saturating_add(l);
+            hi = match (hi, h) {
+                (Some(hi), Some(h)) => hi.checked_add(h),
+                _ => None,
+            };
+        }
+        if self.dedup {
+            lo = lo.min(1);
+        }
+        (lo, hi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmerge() {
+        let shards = vec![
+            vec![1, 4, 7, 10],
+            vec![],
+            vec![2, 2, 5, 8],
+            vec![0, 3, 6, 9, 12],
+        ];
+        let merged: Vec<i32> = kmerge(shards.clone()).collect();
+        let mut expected: Vec<i32> = shards.iter().flatten().copied().collect();
+        expected.sort();
+        assert_eq!(expected, merged);
+        assert_eq!((13, Some(13)), kmerge(shards.clone()).size_hint());
+
+        let merged: Vec<i32> = kmerge_dedup(shards.iter().map(|v| v.iter().copied())).collect();
+        expected.dedup();
+        assert_eq!(expected, merged);
+
+        let merged: Vec<&i32> = kmerge_by(shards.iter().map(|v| v.iter().rev()), |a, b| b.cmp(a))
+            .take(3)
+            .collect();
+        assert_eq!(vec![&12, &10, &9], merged);
+
+        assert_eq!(None, kmerge(Vec::<Vec<i32>>::new()).next());
+    }
+
+    #[test]
+    fn test_kmerge_stable() {
+        // equal keys come out in the order of the iterators
+        let shards = vec![
+            vec![(1, 'a'), (3, 'a')],
+            vec![(1, 'b'), (2, 'b')],
+            vec![(1, 'c')],
+        ];
+        let merged: Vec<char> = kmerge_by(shards.clone(), |a, b| a.0.cmp(&b.0))
+            .map(|(_, c)| c)
+            .collect();
+        assert_eq!(vec!['a', 'b', 'c', 'b', 'a'], merged);
+
+        let merged: Vec<(i32, char)> = kmerge_by_dedup(shards, |a, b| a.0.cmp(&b.0)).collect();
+        assert_eq!(vec![(1, 'a'), (2, 'b'), (3, 'a')], merged);
+    }
+}
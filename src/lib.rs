@@ -3,18 +3,61 @@ pub mod dbgt;
 pub mod timeit;
 
 // mod and struct
+mod rng;
+
 pub mod heap;
 pub use heap::Heap;
 
+pub mod minmaxheap;
+pub use minmaxheap::MinMaxHeap;
+
+pub mod arrayheap;
+pub use arrayheap::ArrayHeap;
+
+pub mod topk;
+pub use topk::{TopK, TopKExt};
+
+pub mod kmerge;
+pub use kmerge::{kmerge, kmerge_by, kmerge_by_dedup, kmerge_dedup, KMerge, KMergeBy};
+
+pub mod runningmedian;
+pub use runningmedian::RunningMedian;
+
+pub mod stableheap;
+pub use stableheap::StableHeap;
+
+pub mod radixheap;
+pub use radixheap::{RadixHeap, RadixKey};
+
+pub mod sharedheap;
+pub use sharedheap::SharedHeap;
+
+pub mod delayqueue;
+pub use delayqueue::DelayQueue;
+
+pub mod externalsort;
+pub use externalsort::{external_sort, Codec, ExternalSort};
+
+pub mod reservoir;
+pub use reservoir::WeightedReservoir;
+
+pub mod huffman;
+
 pub mod linkedlist;
 pub use linkedlist::{LinkedList, ListNode};
 
+pub mod lrucache;
+pub use lrucache::LruCache;
+
 pub mod avl;
 pub use avl::{Avl, AvlNode};
 
 pub mod rbt;
 pub use rbt::{Rbt, RbtNode};
 
+pub mod skiplist;
+pub use skiplist::SkipList;
+
 pub mod timer;
 pub use timer::Timer;
 
@@ -1,106 +1,79 @@
 //! A Doubly Linked List implementation.
 //!
-//! version 0.1.1
+//! version 0.2.5
 //! https://github.com/wufangjie/utils/blob/main/src/linkedlist.rs
 //!
-//! Forward list share the ownership, backward list is just raw pointer
-//! Only used two `unsafe` to save loop time
+//! The list owns its nodes, which are allocated by Box and linked by NonNull
+//! in both directions (PhantomData tells the compiler about the ownership).
+//! Nodes are dropped iteratively, so a long list will not overflow the stack.
+//! The free function remove_node() is removed, since there is no Box link to
+//! take now, use CursorMut::remove_current() instead.
+//!
+//! Test under Miri: cargo +nightly miri test linkedlist
 
 use std::cmp::Ordering;
 use std::fmt;
-use std::ptr;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<ListNode<T>>>;
 
-#[derive(Debug)]
 pub struct LinkedList<T> {
-    head: Option<Box<ListNode<T>>>, // Box own the data they point to
-    tail: *mut ListNode<T>,
+    head: Link<T>,
+    tail: Link<T>,
     len: usize,
+    marker: PhantomData<Box<ListNode<T>>>,
 }
 
 #[derive(Debug)]
 pub struct ListNode<T> {
     data: T,
-    next: Option<Box<ListNode<T>>>,
-    prev: *mut ListNode<T>,
+    next: Link<T>,
+    prev: Link<T>,
 }
 
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
 impl<T> Default for LinkedList<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        // NOTE: iterative, the default recursive drop may overflow the stack
+        while self.pop_front_node().is_some() {}
+    }
+}
+
 impl<T> LinkedList<T> {
     pub fn new() -> Self {
         LinkedList {
             head: None,
-            tail: ptr::null_mut(),
+            tail: None,
             len: 0,
+            marker: PhantomData,
         }
     }
 
-    pub fn push_back_node(&mut self, mut node: Box<ListNode<T>>) {
-        let p = (*node).as_mut_ptr();
-        if self.len == 0 {
-            self.head = Some(node);
-        } else {
-            node.prev = self.tail;
-            unsafe { (*self.tail).next = Some(node) }
-        }
-        self.tail = p;
-        self.len += 1;
+    pub fn push_back_node(&mut self, node: Box<ListNode<T>>) {
+        self.link_node_after(self.tail, node);
     }
 
-    pub fn push_front_node(&mut self, mut node: Box<ListNode<T>>) {
-        if self.len == 0 {
-            self.tail = (*node).as_mut_ptr();
-        } else {
-            if let Some(first) = &mut self.head {
-                first.prev = (*node).as_mut_ptr();
-            }
-            node.next = self.head.take();
-        }
-        self.head = Some(node);
-        self.len += 1;
+    pub fn push_front_node(&mut self, node: Box<ListNode<T>>) {
+        self.link_node_after(None, node);
     }
 
     pub fn pop_back_node(&mut self) -> Option<Box<ListNode<T>>> {
-        let mut ret = None::<Box<ListNode<T>>>;
-        if self.len > 0 {
-            if self.len == 1 {
-                std::mem::swap(&mut ret, &mut self.head);
-                self.tail = ptr::null_mut();
-            } else {
-                let pre; // TODO: immutable pre can have an &mut pre.next
-                unsafe {
-                    pre = &mut *(*self.tail).prev; // not null
-                }
-                std::mem::swap(&mut ret, &mut pre.next);
-                self.tail = pre.as_mut_ptr();
-                if let Some(node) = &mut ret {
-                    node.prev = ptr::null_mut(); // need this?
-                }
-            }
-            self.len -= 1;
-        }
-        ret
+        // SAFETY: tail is a node of this list
+        self.tail.map(|node| unsafe { self.unlink_node(node) })
     }
 
     pub fn pop_front_node(&mut self) -> Option<Box<ListNode<T>>> {
-        let mut ret = None::<Box<ListNode<T>>>;
-        if self.len > 0 {
-            std::mem::swap(&mut ret, &mut self.head);
-            if let Some(node) = &mut ret {
-                std::mem::swap(&mut self.head, &mut node.next);
-            }
-            if let Some(node) = &mut self.head {
-                node.prev = ptr::null_mut(); // need this?
-            } else {
-                self.tail = ptr::null_mut();
-            }
-            self.len -= 1;
-        }
-        ret
+        // SAFETY: head is a node of this list
+        self.head.map(|node| unsafe { self.unlink_node(node) })
     }
 
     pub fn push_back(&mut self, v: T) {
@@ -119,79 +92,117 @@ impl<T> LinkedList<T> {
         self.pop_front_node().map(|node| node.data)
     }
 
-    pub fn remove_node(p: &mut Option<Box<ListNode<T>>>) -> Option<T> {
-        // NOTE: this is not a method, so we can not modify self.len here
-        let mut temp = None::<Box<ListNode<T>>>;
-        std::mem::swap(&mut temp, p);
-        if let Some(to_remove) = &mut temp {
-            std::mem::swap(p, &mut to_remove.next);
-            if let Some(node) = p {
-                node.prev = to_remove.prev;
-            }
-            to_remove.prev = ptr::null_mut(); // need this?
+    pub fn get(&self, i: usize) -> Option<&T> {
+        // SAFETY: i < len, so the node is a node of this list
+        (i < self.len).then(|| unsafe { &(*self.node_at(i).as_ptr()).data })
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        // SAFETY: i < len, so the node is a node of this list
+        (i < self.len).then(|| unsafe { &mut (*self.node_at(i).as_ptr()).data })
+    }
+
+    /// insert v as the i-th one (i <= len), return v back if i > len
+    pub fn insert_at(&mut self, i: usize, v: T) -> Result<(), T> {
+        if i > self.len {
+            return Err(v);
         }
-        temp.map(|node| node.data)
+        let prev = if i == 0 {
+            None
+        } else {
+            Some(self.node_at(i - 1))
+        };
+        self.link_node_after(prev, Box::new(ListNode::new(v)));
+        Ok(())
     }
 
     pub fn remove_at(&mut self, i: usize) -> Option<T> {
-        match i.cmp(&(self.len - 1)) {
-            Ordering::Greater => None,
-            Ordering::Equal => self.pop_back(),
-            Ordering::Less => {
-                let mut p = &mut self.head;
-                for _ in 0..i {
-                    if let Some(node) = p {
-                        p = &mut node.next;
-                    }
-                }
-                self.len -= 1;
-                Self::remove_node(p)
-            }
+        if i >= self.len {
+            return None;
         }
-
-        // if i > self.len - 1 {
-        //     None
-        // } else if i == self.len - 1 {
-        //     self.pop_back()
-        // } else {
-        //     let mut p = &mut self.head;
-        //     for _ in 0..i {
-        //         if let Some(node) = p {
-        //             p = &mut node.next;
-        //         }
-        //     }
-        //     self.len -= 1;
-        //     Self::remove_node(p)
-        // }
+        // SAFETY: i < len, so the node is a node of this list
+        Some(unsafe { self.unlink_node(self.node_at(i)) }.data)
     }
 
+    /// remove the first one equals to item (the head included)
     pub fn remove_item(&mut self, item: T)
     where
         T: PartialEq,
     {
-        let mut p = &mut self.head;
-        while let Some(node) = p {
-            if let Some(next) = &node.next {
-                if next.data == item {
-                    self.len -= 1;
-                    Self::remove_node(&mut node.next);
-                    break;
+        self.remove_first(&item);
+    }
+
+    pub fn remove_first(&mut self, x: &T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        self.extract_if(|y| y == x).next()
+    }
+
+    /// return the number of removed ones
+    pub fn remove_all(&mut self, x: &T) -> usize
+    where
+        T: PartialEq,
+    {
+        self.extract_if(|y| y == x).count()
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.extract_if(|x| !f(x)).for_each(drop);
+    }
+
+    /// remove the consecutive repeated ones, keep the first of every run
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// same(current, previous kept one), the current one is removed if true
+    pub fn dedup_by<F>(&mut self, mut same: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let Some(mut kept) = self.head else {
+            return;
+        };
+        // SAFETY: all the links point to the nodes of this list, and kept is
+        // never the node compared with it
+        unsafe {
+            while let Some(node) = (*kept.as_ptr()).next {
+                if same(&mut (*node.as_ptr()).data, &mut (*kept.as_ptr()).data) {
+                    self.unlink_node(node);
+                } else {
+                    kept = node;
                 }
             }
-            p = &mut node.next;
         }
     }
 
-    // pub fn from_iter<I>(iter: I) -> Self
-    // where
-    //     I: Iterator<Item = T>,
-    // {
-    //     let mut lst = LinkedList::new();
-    //     for item in iter {
-    //         lst.push_back(item);
-    //     }
-    //     lst
-    // }
+    /// a lazy iterator which removes and yields the ones f returns true, the
+    /// rest ones (and the unvisited ones if dropped early) stay in the list
+    pub fn extract_if<F>(&mut self, f: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            cur: self.head,
+            list: self,
+            f,
+        }
+    }
 
     pub fn is_empty(&self) -> bool {
         self.head.is_none()
@@ -206,7 +217,21 @@ impl<T> LinkedList<T> {
     }
 
     pub fn iter(&self) -> Iter<'_, T> {
-        Iter { head: &self.head }
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
     }
 
     pub fn contains(&self, x: &T) -> bool
@@ -215,6 +240,304 @@ impl<T> LinkedList<T> {
     {
         self.iter().any(|v| v == x)
     }
+
+    /// move all the elements of other to the back, O(1)
+    pub fn append(&mut self, other: &mut Self) {
+        self.cursor_back_mut().splice_after(std::mem::take(other));
+    }
+
+    /// move all the elements of other to the front, O(1)
+    pub fn prepend(&mut self, other: &mut Self) {
+        let index = self.len;
+        CursorMut {
+            list: self,
+            cur: None,
+            index,
+        }
+        .splice_after(std::mem::take(other));
+    }
+
+    /// split into [0, at) and [at, len), return the latter,
+    /// walk from the nearer end, panic if at > len
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            return std::mem::take(self);
+        }
+        let cur = Some(self.node_at(at - 1));
+        CursorMut {
+            list: self,
+            cur,
+            index: at - 1,
+        }
+        .split_after()
+    }
+
+    /// stable merge sort, relink the nodes without allocating or moving data
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// NOTE: the nodes are leaked (the list becomes empty) if cmp panics
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut is_less = |a: &T, b: &T| cmp(a, b) == Ordering::Less;
+        let len = std::mem::take(&mut self.len);
+        let mut p = self.head.take();
+        self.tail = None;
+
+        // bottom-up, runs[i] is a sorted run of 2^i nodes (or None), the
+        // higher i, the earlier its nodes, so no allocation is needed
+        let mut runs: [Link<T>; usize::BITS as usize] = [None; usize::BITS as usize];
+        while let Some(node) = p {
+            // SAFETY: all the nodes are owned by this list, only relinked here
+            unsafe {
+                p = (*node.as_ptr()).next.take();
+                let mut carry = Some(node);
+                let mut i = 0;
+                while let Some(run) = runs[i].take() {
+                    carry = Self::merge_links(Some(run), carry, &mut is_less);
+                    i += 1;
+                }
+                runs[i] = carry;
+            }
+        }
+        let mut head = None;
+        for run in runs {
+            head = unsafe { Self::merge_links(run, head, &mut is_less) };
+        }
+        self.relink(head, len);
+    }
+
+    /// merge a sorted other (after self on ties) into the sorted self
+    pub fn merge_sorted(&mut self, other: &mut Self)
+    where
+        T: Ord,
+    {
+        let len = std::mem::take(&mut self.len) + std::mem::take(&mut other.len);
+        let (a, b) = (self.head.take(), other.head.take());
+        self.tail = None;
+        other.tail = None;
+        // SAFETY: the nodes of both lists are taken, and only relinked here
+        let head = unsafe { Self::merge_links(a, b, &mut |x: &T, y: &T| x < y) };
+        self.relink(head, len);
+    }
+
+    /// SAFETY: a and b are disjoint chains (by next) of owned nodes
+    unsafe fn merge_links<F>(mut a: Link<T>, mut b: Link<T>, is_less: &mut F) -> Link<T>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let mut head = None;
+        let mut tail: Link<T> = None;
+        loop {
+            let node = match (a, b) {
+                (Some(x), Some(y)) => {
+                    if is_less(&(*y.as_ptr()).data, &(*x.as_ptr()).data) {
+                        b = (*y.as_ptr()).next;
+                        y
+                    } else {
+                        a = (*x.as_ptr()).next;
+                        x
+                    }
+                }
+                (rest, None) | (None, rest) => {
+                    match tail {
+                        Some(t) => (*t.as_ptr()).next = rest,
+                        None => head = rest,
+                    }
+                    return head;
+                }
+            };
+            match tail {
+                Some(t) => (*t.as_ptr()).next = Some(node),
+                None => head = Some(node),
+            }
+            tail = Some(node);
+        }
+    }
+
+    /// take the chain (by next) from head as the whole list, fix prev and tail
+    fn relink(&mut self, head: Link<T>, len: usize) {
+        self.head = head;
+        self.len = len;
+        let mut prev = None;
+        let mut p = head;
+        while let Some(node) = p {
+            // SAFETY: the chain is owned by this list now
+            unsafe {
+                (*node.as_ptr()).prev = prev;
+                p = (*node.as_ptr()).next;
+            }
+            prev = Some(node);
+        }
+        self.tail = prev;
+    }
+
+    /// relink in place, the data are not moved
+    pub fn reverse(&mut self) {
+        let mut p = self.head;
+        while let Some(node) = p {
+            // SAFETY: all the links point to the nodes of this list
+            unsafe {
+                let node = &mut *node.as_ptr();
+                p = node.next;
+                std::mem::swap(&mut node.next, &mut node.prev);
+            }
+        }
+        std::mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// move the first k (mod len) ones to the back
+    pub fn rotate_left(&mut self, k: usize) {
+        if self.len > 0 {
+            let mut back = self.split_off(k % self.len);
+            self.prepend(&mut back);
+        }
+    }
+
+    /// move the last k (mod len) ones to the front
+    pub fn rotate_right(&mut self, k: usize) {
+        if self.len > 0 {
+            self.rotate_left(self.len - k % self.len);
+        }
+    }
+
+    /// move the nodes into (the ones f returns true, the rest), keep the order
+    pub fn partition<F>(mut self, mut f: F) -> (Self, Self)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let (mut left, mut right) = (Self::new(), Self::new());
+        while let Some(node) = self.pop_front_node() {
+            if f(&node.data) {
+                left.push_back_node(node);
+            } else {
+                right.push_back_node(node);
+            }
+        }
+        (left, right)
+    }
+
+    /// swap the i-th and j-th nodes by relinking, false if out of bounds
+    pub fn swap_nodes(&mut self, i: usize, j: usize) -> bool {
+        if i >= self.len || j >= self.len {
+            return false;
+        }
+        let (i, j) = (i.min(j), i.max(j));
+        if i == j {
+            return true;
+        }
+        let (a, b) = (self.node_at(i), self.node_at(j));
+        // SAFETY: a and b are different nodes of this list
+        unsafe {
+            let (pa, pb) = ((*a.as_ptr()).prev, (*b.as_ptr()).prev);
+            let b = self.unlink_node(b);
+            self.link_node_after(pa, b);
+            if pb != Some(a) {
+                let a = self.unlink_node(a);
+                self.link_node_after(pb, a);
+            }
+        }
+        true
+    }
+
+    /// a cursor at the front element (the "ghost" non-element if empty)
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// a cursor at the back element (the "ghost" non-element if empty)
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    /// the front node (a handle for node-based structures such as LruCache)
+    pub(crate) fn head_node(&self) -> Link<T> {
+        self.head
+    }
+
+    /// the node at index i (i < len), walk from the nearer end
+    fn node_at(&self, i: usize) -> NonNull<ListNode<T>> {
+        // SAFETY: i < len, so all the links followed are not None
+        unsafe {
+            if i < self.len >> 1 {
+                let mut p = self.head.unwrap_unchecked();
+                for _ in 0..i {
+                    p = (*p.as_ptr()).next.unwrap_unchecked();
+                }
+                p
+            } else {
+                let mut p = self.tail.unwrap_unchecked();
+                for _ in i + 1..self.len {
+                    p = (*p.as_ptr()).prev.unwrap_unchecked();
+                }
+                p
+            }
+        }
+    }
+
+    /// link node after `prev` (at the front if `prev` is None)
+    fn link_node_after(&mut self, prev: Link<T>, node: Box<ListNode<T>>) {
+        let node = NonNull::from(Box::leak(node));
+        // SAFETY: prev is None or a node of this list, node is not shared
+        unsafe {
+            let next = match prev {
+                Some(p) => (*p.as_ptr()).next,
+                None => self.head,
+            };
+            (*node.as_ptr()).prev = prev;
+            (*node.as_ptr()).next = next;
+            match prev {
+                Some(p) => (*p.as_ptr()).next = Some(node),
+                None => self.head = Some(node),
+            }
+            match next {
+                Some(n) => (*n.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+        }
+        self.len += 1;
+    }
+
+    /// SAFETY: node must be a node of this list
+    pub(crate) unsafe fn unlink_node(&mut self, node: NonNull<ListNode<T>>) -> Box<ListNode<T>> {
+        let (prev, next) = ((*node.as_ptr()).prev, (*node.as_ptr()).next);
+        match prev {
+            Some(p) => (*p.as_ptr()).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(n) => (*n.as_ptr()).prev = prev,
+            None => self.tail = prev,
+        }
+        self.len -= 1;
+        let mut node = Box::from_raw(node.as_ptr());
+        node.prev = None;
+        node.next = None;
+        node
+    }
 }
 
 impl<T> FromIterator<T> for LinkedList<T> {
@@ -227,6 +550,12 @@ impl<T> FromIterator<T> for LinkedList<T> {
     }
 }
 
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
 impl<T> fmt::Display for LinkedList<T>
 where
     T: fmt::Display + fmt::Debug,
@@ -234,15 +563,13 @@ where
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "(")?;
         let mut is_first_time = true;
-        let mut head = &self.head;
-        while let Some(p) = head {
+        for data in self.iter() {
             if is_first_time {
                 is_first_time = false
             } else {
                 write!(f, " -> ")?;
             }
-            write!(f, "{}", p.data)?;
-            head = &p.next;
+            write!(f, "{}", data)?;
         }
         write!(f, ")")
     }
@@ -253,42 +580,381 @@ impl<T> ListNode<T> {
         ListNode {
             data: item,
             next: None,
-            prev: ptr::null_mut(),
+            prev: None,
         }
     }
 
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+
+    pub fn into_data(self) -> T {
+        self.data
+    }
+
     pub fn as_ptr(&self) -> *const Self {
         self as *const Self
     }
 
-    pub fn as_mut_ptr(&self) -> *mut Self {
-        // TODO: Does this unsafe?
-        self as *const Self as usize as *mut Self
+    pub fn as_mut_ptr(&mut self) -> *mut Self {
+        self as *mut Self
     }
 }
 
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// `len` is the number of nodes between head and tail (inclusive),
+/// so the two ends never cross
 pub struct Iter<'a, T> {
-    head: &'a Option<Box<ListNode<T>>>,
-    //marker: PhantomData<&'a ListNode<T>>, // NOTE: just for lifetime
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a ListNode<T>>, // NOTE: just for lifetime
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.head {
-            None => None,
-            Some(node) => {
-                self.head = &node.next;
-                Some(&node.data)
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: len > 0, so head is a node borrowed by 'a
+        let node = unsafe { &*self.head?.as_ptr() };
+        self.head = node.next;
+        self.len -= 1;
+        Some(&node.data)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: len > 0, so tail is a node borrowed by 'a
+        let node = unsafe { &*self.tail?.as_ptr() };
+        self.tail = node.prev;
+        self.len -= 1;
+        Some(&node.data)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut ListNode<T>>, // NOTE: just for lifetime
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: len > 0, so head is a node borrowed by 'a, and each node is
+        // yielded only once
+        let node = unsafe { &mut *self.head?.as_ptr() };
+        self.head = node.next;
+        self.len -= 1;
+        Some(&mut node.data)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: the same as next()
+        let node = unsafe { &mut *self.tail?.as_ptr() };
+        self.tail = node.prev;
+        self.len -= 1;
+        Some(&mut node.data)
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+pub struct ExtractIf<'a, T, F> {
+    list: &'a mut LinkedList<T>,
+    cur: Link<T>, // the next one to visit
+    f: F,
+}
+
+impl<T, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while let Some(node) = self.cur {
+            // SAFETY: cur is a node of the list, and it is visited only once
+            unsafe {
+                self.cur = (*node.as_ptr()).next;
+                if (self.f)(&mut (*node.as_ptr()).data) {
+                    return Some(self.list.unlink_node(node).data);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A cursor which can move back and forth and edit the list at any position.
+///
+/// Besides the elements, it can point at a "ghost" non-element (index len),
+/// which sits between the back and the front of the list.
+pub struct CursorMut<'a, T> {
+    list: &'a mut LinkedList<T>,
+    cur: Link<T>, // None for the ghost
+    index: usize,
+}
+
+// SAFETY (for the whole impl): cur is None or a node of self.list, and so are
+// all the links followed from it
+impl<T> CursorMut<'_, T> {
+    /// None if at the ghost
+    pub fn index(&self) -> Option<usize> {
+        self.cur.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        match self.cur {
+            None => {
+                self.cur = self.list.head;
+                self.index = 0;
+            }
+            Some(cur) => {
+                self.cur = unsafe { (*cur.as_ptr()).next };
+                self.index += 1;
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.cur {
+            None => {
+                self.cur = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+            Some(cur) => {
+                self.cur = unsafe { (*cur.as_ptr()).prev };
+                self.index = match self.cur {
+                    None => self.list.len,
+                    Some(_) => self.index - 1,
+                };
+            }
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cur.map(|cur| unsafe { &mut (*cur.as_ptr()).data })
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.cur {
+            None => self.list.head,
+            Some(cur) => unsafe { (*cur.as_ptr()).next },
+        };
+        next.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.prev_node()
+            .map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    /// insert before the current element (at the back if at the ghost)
+    pub fn insert_before(&mut self, v: T) {
+        let prev = self.prev_node();
+        self.list.link_node_after(prev, Box::new(ListNode::new(v)));
+        self.index += 1;
+    }
+
+    /// insert after the current element (at the front if at the ghost)
+    pub fn insert_after(&mut self, v: T) {
+        self.list
+            .link_node_after(self.cur, Box::new(ListNode::new(v)));
+        if self.cur.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    /// remove the current element and move to the next one
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        self.cur = unsafe { (*cur.as_ptr()).next };
+        let node = unsafe { self.list.unlink_node(cur) };
+        if self.cur.is_none() {
+            self.index = self.list.len;
+        }
+        Some(node.data)
+    }
+
+    /// split off the elements before the current one (all if at the ghost)
+    pub fn split_before(&mut self) -> LinkedList<T> {
+        let Some(cur) = self.cur else {
+            self.index = 0;
+            return std::mem::take(self.list);
+        };
+        let Some(prev) = (unsafe { (*cur.as_ptr()).prev }) else {
+            return LinkedList::new();
+        };
+        unsafe {
+            (*prev.as_ptr()).next = None;
+            (*cur.as_ptr()).prev = None;
+        }
+        let mut front = LinkedList::new();
+        front.head = self.list.head.replace(cur);
+        front.tail = Some(prev);
+        front.len = self.index;
+        self.list.len -= self.index;
+        self.index = 0;
+        front
+    }
+
+    /// split off the elements after the current one (all if at the ghost)
+    pub fn split_after(&mut self) -> LinkedList<T> {
+        let Some(cur) = self.cur else {
+            self.index = 0;
+            return std::mem::take(self.list);
+        };
+        let Some(next) = (unsafe { (*cur.as_ptr()).next }) else {
+            return LinkedList::new();
+        };
+        unsafe {
+            (*cur.as_ptr()).next = None;
+            (*next.as_ptr()).prev = None;
+        }
+        let mut back = LinkedList::new();
+        back.head = Some(next);
+        back.tail = self.list.tail.replace(cur);
+        back.len = self.list.len - self.index - 1;
+        self.list.len = self.index + 1;
+        back
+    }
+
+    /// move all the elements of other after the current one
+    /// (at the front if at the ghost)
+    pub fn splice_after(&mut self, mut other: LinkedList<T>) {
+        let (Some(head), Some(tail)) = (other.head.take(), other.tail.take()) else {
+            return;
+        };
+        let len = std::mem::take(&mut other.len);
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => (*cur.as_ptr()).next,
+                None => self.list.head,
+            };
+            (*head.as_ptr()).prev = self.cur;
+            (*tail.as_ptr()).next = next;
+            match self.cur {
+                Some(cur) => (*cur.as_ptr()).next = Some(head),
+                None => self.list.head = Some(head),
             }
+            match next {
+                Some(n) => (*n.as_ptr()).prev = Some(tail),
+                None => self.list.tail = Some(tail),
+            }
+        }
+        self.list.len += len;
+        if self.cur.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    /// the node before the current one (the back if at the ghost)
+    fn prev_node(&self) -> Link<T> {
+        match self.cur {
+            None => self.list.tail,
+            Some(cur) => unsafe { (*cur.as_ptr()).prev },
         }
     }
 }
 
 #[test]
 fn test_linkedlist() {
-    let mut ll = LinkedList::from_iter(vec![9, 2, 3, 4, 5, 6].into_iter());
+    let mut ll = LinkedList::from_iter(vec![9, 2, 3, 4, 5, 6]);
     // let mut ll = LinkedList::new();
     // for v in [4, 5, 6].into_iter() {
     //     ll.push_back(v);
@@ -324,3 +990,441 @@ fn test_linkedlist() {
     // assert!(!ll.contains(&3));
     // assert!(ll.contains(&42));
 }
+
+#[cfg(test)]
+fn check<T: PartialEq + fmt::Debug + Clone>(ll: &LinkedList<T>, expected: &[T]) {
+    assert_eq!(expected.len(), ll.len());
+    assert_eq!(expected, ll.iter().cloned().collect::<Vec<_>>());
+    // walk backward by the prev links
+    let mut back = vec![];
+    let mut p = ll.tail;
+    while let Some(node) = p {
+        unsafe {
+            back.push((*node.as_ptr()).data.clone());
+            p = (*node.as_ptr()).prev;
+        }
+    }
+    back.reverse();
+    assert_eq!(expected, back);
+}
+
+#[test]
+fn test_cursor() {
+    let mut ll = LinkedList::from_iter(1..=5);
+    let mut cur = ll.cursor_front_mut();
+    assert_eq!(Some(0), cur.index());
+    assert_eq!(Some(&mut 1), cur.current());
+    cur.move_prev();
+    assert_eq!(None, cur.index());
+    assert_eq!(None, cur.current());
+    assert_eq!(Some(&mut 1), cur.peek_next());
+    assert_eq!(Some(&mut 5), cur.peek_prev());
+    cur.move_prev();
+    assert_eq!(Some(4), cur.index());
+    cur.move_next();
+    cur.move_next();
+    assert_eq!(Some(&mut 1), cur.current());
+
+    cur.insert_before(0); // at the back
+    cur.insert_after(10);
+    assert_eq!(Some(1), cur.index());
+    *cur.current().unwrap() *= 100;
+    cur.move_next();
+    cur.move_next();
+    assert_eq!(Some(&mut 2), cur.current());
+    cur.insert_after(20);
+    cur.insert_before(15);
+    assert_eq!(Some(4), cur.index());
+    check(&ll, &[0, 100, 10, 15, 2, 20, 3, 4, 5]);
+
+    let mut cur = ll.cursor_front_mut();
+    assert_eq!(Some(0), cur.remove_current());
+    assert_eq!(Some(&mut 100), cur.current());
+    let mut cur = ll.cursor_back_mut();
+    assert_eq!(Some(5), cur.remove_current());
+    assert_eq!(None, cur.index());
+    assert_eq!(None, cur.remove_current());
+    cur.insert_after(-1); // at the front
+    cur.move_prev();
+    assert_eq!(Some(4), cur.remove_current());
+    check(&ll, &[-1, 100, 10, 15, 2, 20, 3]);
+
+    let mut cur = ll.cursor_front_mut();
+    cur.move_next();
+    cur.move_next();
+    let front = cur.split_before();
+    assert_eq!(Some(0), cur.index());
+    cur.move_next();
+    cur.move_next();
+    let back = cur.split_after();
+    check(&front, &[-1, 100]);
+    check(&back, &[20, 3]);
+    check(&ll, &[10, 15, 2]);
+
+    let mut cur = ll.cursor_front_mut();
+    cur.splice_after(back);
+    cur.splice_after(LinkedList::new());
+    cur.move_prev();
+    cur.splice_after(front); // at the front
+    cur.move_prev();
+    cur.splice_after(LinkedList::from_iter([7, 8]));
+    assert_eq!(Some(6), cur.index());
+    assert_eq!(Some(&mut 2), cur.current());
+    cur.move_next();
+    cur.move_next();
+    assert_eq!(Some(8), cur.index());
+    assert_eq!(Some(&mut 8), cur.current());
+    assert!(cur.split_after().is_empty());
+    cur.move_next();
+    let all = cur.split_before();
+    check(&all, &[-1, 100, 10, 20, 3, 15, 2, 7, 8]);
+    check(&ll, &[]);
+
+    let mut ll = LinkedList::new();
+    let mut cur = ll.cursor_back_mut();
+    assert_eq!(None, cur.current());
+    cur.move_next();
+    cur.move_prev();
+    assert_eq!(None, cur.index());
+    cur.insert_before(1);
+    cur.insert_after(0);
+    cur.move_next();
+    assert_eq!(Some(&mut 0), cur.current());
+    assert!(cur.split_before().is_empty());
+    check(&ll, &[0, 1]);
+}
+
+#[test]
+fn test_iterators() {
+    let mut ll: LinkedList<i32> = (1..=3).collect();
+    ll.extend([4, 5, 6]);
+    let mut iter = ll.iter();
+    assert_eq!(6, iter.len());
+    assert_eq!(Some(&1), iter.next());
+    assert_eq!(Some(&6), iter.next_back());
+    assert_eq!(Some(&5), iter.next_back());
+    assert_eq!(3, iter.len());
+    assert_eq!(vec![&2, &3, &4], iter.collect::<Vec<_>>());
+    assert_eq!(
+        vec![6, 5, 4, 3, 2, 1],
+        ll.iter().rev().copied().collect::<Vec<_>>()
+    );
+
+    for x in &mut ll {
+        *x *= 10;
+    }
+    let mut iter = ll.iter_mut();
+    *iter.next_back().unwrap() += 1;
+    *iter.next().unwrap() += 2;
+    assert_eq!(4, iter.len());
+    for x in iter.rev() {
+        *x += 3;
+    }
+    let mut sum = 0;
+    for x in &ll {
+        sum += x;
+    }
+    assert_eq!(225, sum);
+    assert_eq!("(12 -> 23 -> 33 -> 43 -> 53 -> 61)", format!("{ll}"));
+
+    let mut iter = ll.into_iter();
+    assert_eq!(Some(61), iter.next_back());
+    assert_eq!(Some(12), iter.next());
+    assert_eq!(4, iter.len());
+    assert_eq!(vec![53, 43, 33, 23], iter.rev().collect::<Vec<_>>());
+
+    let mut empty = LinkedList::<i32>::new();
+    assert_eq!(None, empty.iter().next_back());
+    assert_eq!(None, empty.iter_mut().next());
+    assert_eq!(0, empty.into_iter().len());
+}
+
+#[test]
+fn test_append_split_off() {
+    let mut a: LinkedList<i32> = (1..=3).collect();
+    let mut b: LinkedList<i32> = (4..=6).collect();
+    a.append(&mut b);
+    check(&a, &[1, 2, 3, 4, 5, 6]);
+    check(&b, &[]);
+    a.append(&mut b);
+    b.append(&mut a);
+    check(&a, &[]);
+    check(&b, &[1, 2, 3, 4, 5, 6]);
+
+    let mut c: LinkedList<i32> = (-2..=0).collect();
+    b.prepend(&mut c);
+    check(&b, &[-2, -1, 0, 1, 2, 3, 4, 5, 6]);
+    check(&c, &[]);
+    c.prepend(&mut b);
+    check(&c, &[-2, -1, 0, 1, 2, 3, 4, 5, 6]);
+
+    for at in 0..=9 {
+        let mut front: LinkedList<i32> = (0..9).collect();
+        let mut back = front.split_off(at);
+        check(&front, &(0..at as i32).collect::<Vec<_>>());
+        check(&back, &(at as i32..9).collect::<Vec<_>>());
+        // the pieces still work as lists
+        back.push_front(-1);
+        front.push_back(-2);
+        front.append(&mut back);
+        let mut expected: Vec<i32> = (0..at as i32).collect();
+        expected.extend([-2, -1]);
+        expected.extend(at as i32..9);
+        check(&front, &expected);
+    }
+
+    let mut empty = LinkedList::<i32>::new();
+    check(&empty.split_off(0), &[]);
+}
+
+#[test]
+#[should_panic]
+fn test_split_off_out_of_bounds() {
+    let mut ll: LinkedList<i32> = (0..3).collect();
+    ll.split_off(4);
+}
+
+#[test]
+fn test_drop_long_list() {
+    // the recursive drop of nested Box overflowed the stack here
+    let n = if cfg!(miri) { 1000 } else { 1_000_000 };
+    let ll: LinkedList<usize> = (0..n).collect();
+    assert_eq!(n, ll.len());
+    drop(ll);
+
+    let rc = std::rc::Rc::new(());
+    let mut ll: LinkedList<_> = (0..10).map(|_| std::rc::Rc::clone(&rc)).collect();
+    ll.pop_back_node();
+    drop(ll.split_off(3));
+    assert_eq!(4, std::rc::Rc::strong_count(&rc));
+    drop(ll);
+    assert_eq!(1, std::rc::Rc::strong_count(&rc));
+}
+
+#[test]
+fn test_sort() {
+    let mut rng = crate::rng::Rng::new(45);
+    let mut rand = move || rng.next_u64() as usize;
+    for n in [
+        0,
+        1,
+        2,
+        3,
+        7,
+        8,
+        9,
+        100,
+        if cfg!(miri) { 200 } else { 5000 },
+    ] {
+        let data: Vec<(usize, usize)> = (0..n).map(|i| (rand() % 10, i)).collect();
+        let mut ll: LinkedList<_> = data.iter().copied().collect();
+        let first = ll.head;
+        ll.sort_by_key(|x| x.0); // stable, the second ones keep increasing
+        let mut expected = data.clone();
+        expected.sort_by_key(|x| x.0);
+        check(&ll, &expected);
+        if n > 0 {
+            // relinked, not moved
+            assert!(ll
+                .iter()
+                .any(|x| std::ptr::eq(x, unsafe { &(*first.unwrap().as_ptr()).data })));
+        }
+
+        ll.sort_by(|a, b| b.cmp(a));
+        expected.sort_by(|a, b| b.cmp(a));
+        check(&ll, &expected);
+        ll.sort();
+        expected.sort();
+        check(&ll, &expected);
+    }
+}
+
+#[test]
+fn test_merge_sorted() {
+    let mut a: LinkedList<(i32, char)> = [(1, 'a'), (3, 'a'), (3, 'b'), (7, 'a')]
+        .into_iter()
+        .collect();
+    let mut b: LinkedList<(i32, char)> = [(0, 'c'), (3, 'a'), (8, 'c')].into_iter().collect();
+    a.merge_sorted(&mut b);
+    check(&b, &[]);
+    check(
+        &a,
+        &[
+            (0, 'c'),
+            (1, 'a'),
+            (3, 'a'),
+            (3, 'a'),
+            (3, 'b'),
+            (7, 'a'),
+            (8, 'c'),
+        ],
+    );
+
+    let mut a: LinkedList<i32> = LinkedList::new();
+    let mut b: LinkedList<i32> = (0..3).collect();
+    a.merge_sorted(&mut b);
+    check(&a, &[0, 1, 2]);
+    a.merge_sorted(&mut b);
+    check(&a, &[0, 1, 2]);
+    a.push_back(3);
+    check(&a, &[0, 1, 2, 3]);
+}
+
+#[test]
+fn test_remove_where() {
+    let mut ll: LinkedList<i32> = [1, 2, 1, 3, 1].into_iter().collect();
+    ll.remove_item(1);
+    check(&ll, &[2, 1, 3, 1]);
+    assert_eq!(Some(1), ll.remove_first(&1));
+    check(&ll, &[2, 3, 1]);
+    assert_eq!(Some(1), ll.remove_first(&1)); // tail
+    check(&ll, &[2, 3]);
+    assert_eq!(None, ll.remove_first(&1));
+
+    let mut ll: LinkedList<i32> = [1, 1, 2, 1, 3, 1, 1].into_iter().collect();
+    assert_eq!(5, ll.remove_all(&1));
+    check(&ll, &[2, 3]);
+    assert_eq!(2, ll.remove_all(&2) + ll.remove_all(&3));
+    check(&ll, &[]);
+    ll.push_back(4);
+    check(&ll, &[4]);
+
+    let mut ll: LinkedList<i32> = (0..10).collect();
+    ll.retain(|x| x % 3 == 1);
+    check(&ll, &[1, 4, 7]);
+    ll.retain(|_| false);
+    check(&ll, &[]);
+
+    let mut ll: LinkedList<i32> = (0..10).collect();
+    {
+        let mut extracted = ll.extract_if(|x| *x % 2 == 0);
+        assert_eq!(Some(0), extracted.next());
+        assert_eq!(Some(2), extracted.next());
+    } // the rest stay
+    check(&ll, &[1, 3, 4, 5, 6, 7, 8, 9]);
+    let evens: Vec<i32> = ll.extract_if(|x| *x % 2 == 0).collect();
+    assert_eq!(vec![4, 6, 8], evens);
+    check(&ll, &[1, 3, 5, 7, 9]);
+    let all: Vec<i32> = ll
+        .extract_if(|x| {
+            *x *= 10;
+            true
+        })
+        .collect();
+    assert_eq!(vec![10, 30, 50, 70, 90], all);
+    check(&ll, &[]);
+}
+
+#[test]
+fn test_dedup() {
+    let mut ll: LinkedList<i32> = [1, 1, 2, 2, 2, 1, 3, 3].into_iter().collect();
+    ll.dedup();
+    check(&ll, &[1, 2, 1, 3]);
+    ll.dedup_by_key(|x| *x % 2);
+    check(&ll, &[1, 2, 1]);
+
+    let mut ll: LinkedList<i32> = [5, 5, 5].into_iter().collect();
+    ll.dedup();
+    check(&ll, &[5]);
+    let mut ll: LinkedList<i32> = LinkedList::new();
+    ll.dedup();
+    check(&ll, &[]);
+
+    // compared with the previous kept one, not the removed one
+    let mut ll: LinkedList<i32> = [1, 2, 3, 4, 5].into_iter().collect();
+    ll.dedup_by(|a, b| *a - *b < 2);
+    check(&ll, &[1, 3, 5]);
+}
+
+#[test]
+fn test_indexed() {
+    let mut ll: LinkedList<i32> = LinkedList::new();
+    assert_eq!(None, ll.get(0));
+    assert_eq!(None, ll.get_mut(0));
+    assert_eq!(None, ll.remove_at(0));
+    assert_eq!(Err(1), ll.insert_at(1, 1));
+    assert_eq!(Ok(()), ll.insert_at(0, 1));
+    assert_eq!(Ok(()), ll.insert_at(0, 0));
+    assert_eq!(Ok(()), ll.insert_at(2, 3));
+    assert_eq!(Ok(()), ll.insert_at(2, 2));
+    check(&ll, &[0, 1, 2, 3]);
+
+    let mut ll: LinkedList<i32> = (0..9).collect();
+    let mut expected: Vec<i32> = (0..9).collect();
+    for (i, x) in expected.iter().enumerate() {
+        assert_eq!(Some(x), ll.get(i));
+    }
+    assert_eq!(None, ll.get(9));
+    *ll.get_mut(7).unwrap() += 10; // from the back
+    *ll.get_mut(1).unwrap() += 10;
+    expected[7] += 10;
+    expected[1] += 10;
+    check(&ll, &expected);
+
+    for (i, v) in [(9, 90), (6, 60), (3, 30)] {
+        ll.insert_at(i, v).unwrap();
+        expected.insert(i, v);
+        check(&ll, &expected);
+    }
+    for i in [11, 0, 6, 2, 100] {
+        let x = (i < expected.len()).then(|| expected.remove(i));
+        assert_eq!(x, ll.remove_at(i));
+        check(&ll, &expected);
+    }
+}
+
+#[test]
+fn test_reverse_rotate() {
+    let mut ll: LinkedList<i32> = LinkedList::new();
+    ll.reverse();
+    ll.rotate_left(3);
+    ll.rotate_right(3);
+    check(&ll, &[]);
+    ll.push_back(1);
+    ll.reverse();
+    check(&ll, &[1]);
+
+    let mut ll: LinkedList<i32> = (0..5).collect();
+    ll.reverse();
+    check(&ll, &[4, 3, 2, 1, 0]);
+    ll.reverse();
+    check(&ll, &[0, 1, 2, 3, 4]);
+
+    ll.rotate_left(2);
+    check(&ll, &[2, 3, 4, 0, 1]);
+    ll.rotate_right(3);
+    check(&ll, &[4, 0, 1, 2, 3]);
+    ll.rotate_left(5);
+    check(&ll, &[4, 0, 1, 2, 3]);
+    ll.rotate_right(11);
+    check(&ll, &[3, 4, 0, 1, 2]);
+    ll.rotate_left(0);
+    check(&ll, &[3, 4, 0, 1, 2]);
+}
+
+#[test]
+fn test_partition_swap() {
+    let ll: LinkedList<i32> = (0..10).collect();
+    let (even, odd) = ll.partition(|x| x % 2 == 0);
+    check(&even, &[0, 2, 4, 6, 8]);
+    check(&odd, &[1, 3, 5, 7, 9]);
+    let (all, none) = even.partition(|_| true);
+    check(&all, &[0, 2, 4, 6, 8]);
+    check(&none, &[]);
+
+    let mut ll = all;
+    let mut expected = vec![0, 2, 4, 6, 8];
+    for (i, j) in [(0, 4), (1, 2), (3, 2), (0, 1), (4, 3), (1, 3), (2, 2)] {
+        assert!(ll.swap_nodes(i, j));
+        expected.swap(i, j);
+        check(&ll, &expected);
+    }
+    assert!(!ll.swap_nodes(0, 5));
+    assert!(!LinkedList::<i32>::new().swap_nodes(0, 0));
+
+    let mut ll: LinkedList<i32> = (0..2).collect();
+    let first = ll.get(0).unwrap() as *const i32;
+    assert!(ll.swap_nodes(1, 0));
+    check(&ll, &[1, 0]);
+    assert!(std::ptr::eq(first, ll.get(1).unwrap())); // relinked, not moved
+}
@@ -0,0 +1,224 @@
+//! A LRU cache built on HashMap and the node handles of LinkedList.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/lrucache.rs
+//!
+//! The list keeps (key, value) from the most to the least recently used, and
+//! the map points every key to its node, so all the operations are O(1).
+//! A promoted node is unlinked and pushed to the front again, it is never
+//! reallocated.
+
+use crate::linkedlist::{LinkedList, ListNode};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::ptr::NonNull;
+
+type Evict<K, V> = Box<dyn FnMut(K, V)>;
+
+pub struct LruCache<K, V> {
+    map: HashMap<K, NonNull<ListNode<(K, V)>>>,
+    list: LinkedList<(K, V)>,
+    cap: usize,
+    on_evict: Option<Evict<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(cap: usize) -> Self {
+        LruCache {
+            map: HashMap::with_capacity(cap),
+            list: LinkedList::new(),
+            cap,
+            on_evict: None,
+        }
+    }
+
+    /// f is called with every entry evicted by put() or resize()
+    pub fn with_evict<F: FnMut(K, V) + 'static>(cap: usize, f: F) -> Self {
+        let mut cache = Self::new(cap);
+        cache.on_evict = Some(Box::new(f));
+        cache
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn contains(&self, k: &K) -> bool {
+        self.map.contains_key(k)
+    }
+
+    /// get without promoting
+    pub fn peek(&self, k: &K) -> Option<&V> {
+        // SAFETY: the nodes in map are owned by self.list
+        self.map
+            .get(k)
+            .map(|node| unsafe { &(*node.as_ptr()).data().1 })
+    }
+
+    /// get and promote to the most recently used
+    pub fn get(&mut self, k: &K) -> Option<&V> {
+        self.get_mut(k).map(|v| &*v)
+    }
+
+    pub fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        let node = self.promote(k)?;
+        // SAFETY: the node is owned by self.list, which is borrowed mutably
+        Some(unsafe { &mut (*node.as_ptr()).data_mut().1 })
+    }
+
+    /// return the old value if k is present, the least recently used entry is
+    /// evicted if it is full
+    pub fn put(&mut self, k: K, v: V) -> Option<V> {
+        if let Some(node) = self.promote(&k) {
+            // SAFETY: the node is owned by self.list
+            return Some(std::mem::replace(
+                unsafe { &mut (*node.as_ptr()).data_mut().1 },
+                v,
+            ));
+        }
+        if self.cap == 0 {
+            self.evict(k, v);
+            return None;
+        }
+        if self.len() == self.cap {
+            let (k, v) = self.pop_lru().unwrap();
+            self.evict(k, v);
+        }
+        self.list
+            .push_front_node(Box::new(ListNode::new((k.clone(), v))));
+        self.map.insert(k, self.list.head_node().unwrap());
+        None
+    }
+
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        let node = self.map.remove(k)?;
+        // SAFETY: the node was in map, so it is a node of self.list
+        Some(unsafe { self.list.unlink_node(node) }.into_data().1)
+    }
+
+    /// remove the least recently used entry, without calling the callback
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (k, v) = self.list.pop_back_node()?.into_data();
+        self.map.remove(&k);
+        Some((k, v))
+    }
+
+    /// the least recently used ones are evicted if it shrinks
+    pub fn resize(&mut self, cap: usize) {
+        while self.len() > cap {
+            let (k, v) = self.pop_lru().unwrap();
+            self.evict(k, v);
+        }
+        self.cap = cap;
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.list.clear();
+    }
+
+    /// from the most to the least recently used
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.list.iter().map(|(k, v)| (k, v))
+    }
+
+    /// move the node of k to the front, return the new handle
+    fn promote(&mut self, k: &K) -> Option<NonNull<ListNode<(K, V)>>> {
+        let node = self.map.get_mut(k)?;
+        // SAFETY: the node is in map, so it is a node of self.list
+        let boxed = unsafe { self.list.unlink_node(*node) };
+        self.list.push_front_node(boxed);
+        *node = self.list.head_node().unwrap();
+        Some(*node)
+    }
+
+    fn evict(&mut self, k: K, v: V) {
+        if let Some(f) = self.on_evict.as_mut() {
+            f(k, v);
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.list.iter().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_lru_cache() {
+        let mut cache = LruCache::new(3);
+        assert_eq!(None, cache.put("a", 1));
+        assert_eq!(None, cache.put("b", 2));
+        assert_eq!(None, cache.put("c", 3));
+        assert_eq!(Some(&1), cache.get(&"a")); // a b c
+        assert_eq!(Some(&2), cache.peek(&"b")); // not promoted
+        assert_eq!(None, cache.put("d", 4)); // b is evicted
+        assert!(!cache.contains(&"b"));
+        assert_eq!(r#"{"d": 4, "a": 1, "c": 3}"#, format!("{cache:?}"));
+
+        assert_eq!(Some(3), cache.put("c", 30));
+        *cache.get_mut(&"a").unwrap() += 10;
+        let order: Vec<_> = cache.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(vec![("a", 11), ("c", 30), ("d", 4)], order);
+
+        assert_eq!(Some(("d", 4)), cache.pop_lru());
+        assert_eq!(Some(30), cache.remove(&"c"));
+        assert_eq!(None, cache.remove(&"c"));
+        assert_eq!(1, cache.len());
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(None, cache.pop_lru());
+        assert_eq!(None, cache.get(&"a"));
+    }
+
+    #[test]
+    fn test_lru_cache_evict() {
+        let evicted = Rc::new(RefCell::new(vec![]));
+        let log = Rc::clone(&evicted);
+        let mut cache = LruCache::with_evict(2, move |k, v| log.borrow_mut().push((k, v)));
+        for i in 0..5 {
+            cache.put(i, i * i);
+            cache.get(&0); // 0 is always kept
+        }
+        assert_eq!(vec![(1, 1), (2, 4), (3, 9)], *evicted.borrow());
+
+        cache.resize(5);
+        cache.put(5, 25);
+        cache.put(6, 36);
+        assert_eq!(3, evicted.borrow().len());
+        cache.resize(1);
+        assert_eq!(
+            vec![(1, 1), (2, 4), (3, 9), (4, 16), (0, 0), (5, 25)],
+            *evicted.borrow()
+        );
+        assert_eq!(vec![(&6, &36)], cache.iter().collect::<Vec<_>>());
+
+        cache.pop_lru(); // no callback
+        cache.resize(0);
+        cache.put(7, 49);
+        assert!(cache.is_empty());
+        assert_eq!(Some(&(7, 49)), evicted.borrow().last());
+        assert_eq!(7, evicted.borrow().len());
+    }
+}
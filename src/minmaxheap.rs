@@ -0,0 +1,276 @@
+//! A Min-Max Heap (double-ended priority queue) implementation.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/minmaxheap.rs
+//!
+//! Nodes on even levels (root is level 0) are no greater than all of their
+//! descendants, nodes on odd levels are no less than all of their descendants,
+//! so the min is the root and the max is one of the root's children.
+
+#[derive(Debug)]
+pub struct MinMaxHeap<T: PartialOrd> {
+    data: Vec<T>,
+}
+
+impl<T: PartialOrd> Default for MinMaxHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MinMaxHeap<T>
+where
+    T: PartialOrd,
+{
+    pub fn new() -> Self {
+        Self { data: vec![] }
+    }
+
+    pub fn with_capacity(capacity: usize) -> MinMaxHeap<T> {
+        Self {
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.data.push(item);
+        self.bubble_up(self.len() - 1);
+    }
+
+    pub fn peek_min(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn peek_max(&self) -> Option<&T> {
+        self.max_index().map(|i| &self.data[i])
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            let ret = self.data.swap_remove(0);
+            if !self.is_empty() {
+                self.trickle_down(0);
+            }
+            Some(ret)
+        }
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        let i = self.max_index()?;
+        let ret = self.data.swap_remove(i);
+        if i < self.len() {
+            self.trickle_down(i);
+        }
+        Some(ret)
+    }
+
+    /// push then pop_max, the natural operation for a bounded buffer
+    pub fn push_pop_max(&mut self, mut item: T) -> T {
+        if let Some(i) = self.max_index() {
+            if item < self.data[i] {
+                std::mem::swap(&mut item, &mut self.data[i]);
+                if i > 0 && self.data[i] < self.data[0] {
+                    self.data.swap(i, 0);
+                }
+                self.trickle_down(i);
+            }
+        }
+        item
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.data
+    }
+
+    fn max_index(&self) -> Option<usize> {
+        match self.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.data[1] < self.data[2] { 2 } else { 1 }),
+        }
+    }
+
+    #[inline]
+    fn is_min_level(i: usize) -> bool {
+        (usize::BITS - 1 - (i + 1).leading_zeros()) & 1 == 0
+    }
+
+    /// compare by the order of the level (`<` on min levels, `>` on max levels)
+    #[inline]
+    fn before(&self, i: usize, j: usize, is_min: bool) -> bool {
+        if is_min {
+            self.data[i] < self.data[j]
+        } else {
+            self.data[i] > self.data[j]
+        }
+    }
+
+    fn bubble_up(&mut self, i: usize) {
+        if i == 0 {
+            return;
+        }
+        let is_min = Self::is_min_level(i);
+        let p = (i - 1) >> 1;
+        if self.before(p, i, is_min) {
+            self.data.swap(i, p);
+            self.bubble_up_grandparent(p, !is_min);
+        } else {
+            self.bubble_up_grandparent(i, is_min);
+        }
+    }
+
+    fn bubble_up_grandparent(&mut self, mut i: usize, is_min: bool) {
+        while i > 2 {
+            let g = (((i - 1) >> 1) - 1) >> 1;
+            if self.before(i, g, is_min) {
+                self.data.swap(i, g);
+                i = g;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn trickle_down(&mut self, mut i: usize) {
+        let is_min = Self::is_min_level(i);
+        let n = self.len();
+        loop {
+            // find the first (by level order) of children and grandchildren
+            let c = (i << 1) + 1;
+            if c >= n {
+                return;
+            }
+            let mut m = c;
+            for j in [
+                c + 1,
+                (c << 1) + 1,
+                (c << 1) + 2,
+                (c << 1) + 3,
+                (c << 1) + 4,
+            ] {
+                if j < n && self.before(j, m, is_min) {
+                    m = j;
+                }
+            }
+            if !self.before(m, i, is_min) {
+                return;
+            }
+            self.data.swap(i, m);
+            if m <= c + 1 {
+                return; // m is a child
+            }
+            let p = (m - 1) >> 1;
+            if self.before(p, m, is_min) {
+                self.data.swap(m, p);
+            }
+            i = m;
+        }
+    }
+}
+
+impl<T: PartialOrd> From<Vec<T>> for MinMaxHeap<T> {
+    fn from(data: Vec<T>) -> Self {
+        let mut res = Self { data };
+        for i in (0..res.len() >> 1).rev() {
+            res.trickle_down(i);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_a_min_max_heap<T: PartialOrd + std::fmt::Debug>(heap: &MinMaxHeap<T>) {
+        for i in 1..heap.len() {
+            let mut j = i;
+            while j > 0 {
+                j = (j - 1) >> 1;
+                if MinMaxHeap::<T>::is_min_level(j) {
+                    assert!(heap.data[j] <= heap.data[i], "{:?}", heap.data);
+                } else {
+                    assert!(heap.data[j] >= heap.data[i], "{:?}", heap.data);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_max_heap() {
+        let heap = MinMaxHeap::from(vec![4, 9, 7, 3, 1, 8, 6, 0, 5, 2, 0]);
+        is_a_min_max_heap(&heap);
+        assert_eq!(Some(&0), heap.peek_min());
+        assert_eq!(Some(&9), heap.peek_max());
+
+        let mut heap = MinMaxHeap::from(Vec::<i32>::new());
+        assert_eq!(None, heap.peek_max());
+        assert_eq!(None, heap.pop_max());
+        assert_eq!(None, heap.pop_min());
+        assert_eq!(42, heap.push_pop_max(42));
+
+        let mut heap = MinMaxHeap::with_capacity(12);
+        for i in [11, 10, 4, 9, 7, 3, 1, 8, 6, 0, 5, 2] {
+            heap.push(i);
+            is_a_min_max_heap(&heap);
+        }
+        assert_eq!(Some(11), heap.pop_max());
+        assert_eq!(Some(0), heap.pop_min());
+        assert_eq!(42, heap.push_pop_max(42));
+        assert_eq!(10, heap.push_pop_max(-1));
+        is_a_min_max_heap(&heap);
+        assert_eq!(Some(-1), heap.pop_min());
+        assert_eq!(Some(9), heap.pop_max());
+        assert_eq!(8, heap.len());
+    }
+
+    #[test]
+    fn test_min_max_heap_random() {
+        let mut seed = 42u64;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) % 1000
+        };
+        let mut heap = MinMaxHeap::from((0..100).map(|_| rand()).collect::<Vec<_>>());
+        is_a_min_max_heap(&heap);
+        let mut sorted = heap.data.clone();
+        sorted.sort();
+        for _ in 0..2000 {
+            match rand() % 4 {
+                0 => {
+                    let x = rand();
+                    heap.push(x);
+                    sorted.push(x);
+                    sorted.sort();
+                }
+                1 => assert_eq!(
+                    heap.pop_min(),
+                    (!sorted.is_empty()).then(|| sorted.remove(0))
+                ),
+                2 => assert_eq!(heap.pop_max(), sorted.pop()),
+                _ => {
+                    let x = rand();
+                    sorted.push(x);
+                    sorted.sort();
+                    assert_eq!(Some(heap.push_pop_max(x)), sorted.pop());
+                }
+            }
+            is_a_min_max_heap(&heap);
+            assert_eq!(sorted.first(), heap.peek_min());
+            assert_eq!(sorted.last(), heap.peek_max());
+        }
+    }
+}
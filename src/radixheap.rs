@@ -0,0 +1,164 @@
+//! A monotone Radix Heap implementation (e.g. for Dijkstra with integer weights).
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/radixheap.rs
+//!
+//! Monotone: a pushed key must be no less than the last popped key.
+//! Items are put into buckets by the highest bit that differs from the last
+//! popped key, so every item is moved at most BITS times (amortized O(BITS)).
+
+pub trait RadixKey: Copy + Ord + Default {
+    const BITS: u32;
+
+    /// the number of significant bits of (self ^ other)
+    fn radix_distance(self, other: Self) -> usize;
+}
+
+macro_rules! impl_radix_key {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BITS: u32 = <$t>::BITS;
+
+                #[inline]
+                fn radix_distance(self, other: Self) -> usize {
+                    (<$t>::BITS - (self ^ other).leading_zeros()) as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key!(u8, u16, u32, u64, u128, usize);
+
+#[derive(Debug)]
+pub struct RadixHeap<K: RadixKey, V> {
+    buckets: Vec<Vec<(K, V)>>,
+    last: K,
+    len: usize,
+}
+
+impl<K: RadixKey, V> Default for RadixHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: RadixKey, V> RadixHeap<K, V> {
+    pub fn new() -> Self {
+        RadixHeap {
+            buckets: (0..=K::BITS).map(|_| vec![]).collect(),
+            last: K::default(),
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// panic if key is less than the last popped key
+    pub fn push(&mut self, key: K, value: V) {
+        assert!(
+            key >= self.last,
+            "RadixHeap is monotone: key is less than the last popped key"
+        );
+        self.buckets[key.radix_distance(self.last)].push((key, value));
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        if self.buckets[0].is_empty() {
+            let i = self.buckets.iter().position(|b| !b.is_empty())?;
+            let bucket = std::mem::take(&mut self.buckets[i]);
+            self.last = bucket.iter().map(|(k, _)| *k).min().unwrap();
+            for (k, v) in bucket {
+                // always goes to a lower bucket
+                self.buckets[k.radix_distance(self.last)].push((k, v));
+            }
+        }
+        self.len -= 1;
+        self.buckets[0].pop()
+    }
+
+    pub fn peek_key(&self) -> Option<K> {
+        if self.buckets[0].is_empty() {
+            self.buckets
+                .iter()
+                .find(|b| !b.is_empty())
+                .and_then(|b| b.iter().map(|(k, _)| *k).min())
+        } else {
+            Some(self.last)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heap::Heap;
+
+    #[test]
+    fn test_radix_heap() {
+        let mut heap = RadixHeap::new();
+        assert_eq!(None, heap.pop());
+        assert_eq!(None, heap.peek_key());
+        for (k, v) in [(5u32, 'a'), (3, 'b'), (9, 'c'), (3, 'd'), (0, 'e')] {
+            heap.push(k, v);
+        }
+        assert_eq!(5, heap.len());
+        assert_eq!(Some(0), heap.peek_key());
+        assert_eq!(Some((0, 'e')), heap.pop());
+        assert_eq!(Some(3), heap.peek_key());
+        assert_eq!(3, heap.pop().unwrap().0);
+        heap.push(3, 'f');
+        heap.push(u32::MAX, 'g');
+        let keys: Vec<u32> = std::iter::from_fn(|| heap.pop()).map(|(k, _)| k).collect();
+        assert_eq!(vec![3, 3, 5, 9, u32::MAX], keys);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_radix_heap_not_monotone() {
+        let mut heap = RadixHeap::new();
+        heap.push(5u8, ());
+        heap.pop();
+        heap.push(4, ());
+    }
+
+    #[test]
+    fn test_radix_heap_random() {
+        let mut seed = 33u64;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            seed >> 33
+        };
+        let mut radix = RadixHeap::new();
+        let mut heap = Heap::new();
+        let mut last = 0u64;
+        for i in 0..100000 {
+            if rand() % 5 < 2 {
+                let a = radix.pop().map(|(k, _)| k);
+                let b = heap.pop();
+                assert_eq!(b, a);
+                if let Some(k) = a {
+                    last = k;
+                }
+            } else {
+                // like relaxing an edge in Dijkstra
+                let k = last + rand() % 1000;
+                radix.push(k, i);
+                heap.push(k);
+            }
+            assert_eq!(heap.len(), radix.len());
+            assert_eq!(heap.peek().copied(), radix.peek_key());
+        }
+    }
+}
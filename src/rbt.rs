@@ -488,6 +488,7 @@ mod tests {
         let a = &"hello world".to_string();
         let b = std::array::from_ref(&a);
         crate::dbgt!(&b);
+        #[allow(clippy::cloned_ref_to_slice_refs)]
         let c = &[a.clone()];
         crate::dbgt!(&c);
     }
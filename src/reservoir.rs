@@ -0,0 +1,166 @@
+//! Weighted reservoir sampling (A-Res with A-ExpJ jumps) built on Heap.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/reservoir.rs
+//!
+//! Every item gets a key u^(1/w) (u is uniform in (0, 1), w is its weight),
+//! and the k items with the largest keys are kept in a min Heap (A-Res).
+//! Once the reservoir is full, the exponential jumps of A-ExpJ decide how much
+//! weight to skip before the next replacement, so only O(k log(n/k)) random
+//! numbers are needed.
+//! Keys are kept as ln(u) / w to avoid underflow on small weights.
+
+use crate::heap::Heap;
+use crate::rng::Rng;
+use std::cmp::Ordering;
+
+#[derive(Debug)]
+struct Keyed<T> {
+    key: f64,
+    item: T,
+}
+
+impl<T> PartialEq for Keyed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T> PartialOrd for Keyed<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+#[derive(Debug)]
+pub struct WeightedReservoir<T> {
+    heap: Heap<Keyed<T>>,
+    k: usize,
+    rng: Rng,
+    skip: f64, // the weight left to skip before the next replacement
+}
+
+impl<T> WeightedReservoir<T> {
+    pub fn new(k: usize, seed: u64) -> Self {
+        WeightedReservoir {
+            heap: Heap::with_capacity(k),
+            k,
+            rng: Rng::new(seed),
+            skip: 0.0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// items with a non-positive (or NaN) weight are never sampled
+    pub fn offer(&mut self, item: T, weight: f64) {
+        if weight.is_nan() || weight <= 0.0 || self.k == 0 {
+            return;
+        }
+        if self.heap.len() < self.k {
+            let key = self.rng.next_f64().ln() / weight;
+            self.heap.push(Keyed { key, item });
+            if self.heap.len() == self.k {
+                self.jump();
+            }
+            return;
+        }
+        self.skip -= weight;
+        if self.skip <= 0.0 {
+            // the new key is uniform in (threshold, 1) after powering by w
+            let t = (self.threshold() * weight).exp();
+            let u = t + (1.0 - t) * self.rng.next_f64();
+            let key = u.ln() / weight;
+            self.heap.replace(Keyed { key, item });
+            self.jump();
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.heap.iter().map(|x| &x.item)
+    }
+
+    /// the sampled items, in no particular order
+    pub fn into_vec(self) -> Vec<T> {
+        self.heap.into_inner().into_iter().map(|x| x.item).collect()
+    }
+
+    /// the smallest key in the reservoir (in log space)
+    fn threshold(&self) -> f64 {
+        self.heap.peek().map_or(f64::NEG_INFINITY, |x| x.key)
+    }
+
+    fn jump(&mut self) {
+        self.skip = self.rng.next_f64().ln() / self.threshold();
+    }
+}
+
+impl<T> Extend<(T, f64)> for WeightedReservoir<T> {
+    fn extend<I: IntoIterator<Item = (T, f64)>>(&mut self, iter: I) {
+        for (item, weight) in iter {
+            self.offer(item, weight);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservoir() {
+        let mut res = WeightedReservoir::new(5, 42);
+        res.extend([('a', 1.0), ('b', 0.0), ('c', -1.0), ('d', f64::NAN)]);
+        assert_eq!(vec!['a'], res.into_vec());
+
+        let sample = |seed| {
+            let mut res = WeightedReservoir::new(10, seed);
+            res.extend((0..1000).map(|i| (i, 1.0 + (i % 7) as f64)));
+            let mut v = res.into_vec();
+            v.sort();
+            v
+        };
+        assert_eq!(10, sample(1).len());
+        assert_eq!(sample(1), sample(1));
+        assert_ne!(sample(1), sample(2));
+
+        let mut res = WeightedReservoir::new(0, 42);
+        res.offer(1, 1.0);
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn test_reservoir_weighting() {
+        // k = 1: every item is sampled with probability w / sum(w)
+        let weights = [1.0, 2.0, 3.0, 4.0, 10.0];
+        let mut counts = [0usize; 5];
+        let n = 20000;
+        for seed in 0..n {
+            let mut res = WeightedReservoir::new(1, seed);
+            res.extend(weights.iter().enumerate().map(|(i, &w)| (i, w)));
+            counts[res.into_vec()[0]] += 1;
+        }
+        for (c, w) in counts.iter().zip(weights) {
+            let expected = w / 20.0;
+            let freq = *c as f64 / n as f64;
+            assert!((freq - expected).abs() < 0.015, "{freq} vs {expected}");
+        }
+
+        // k = 2 over a long stream: the heavy items are (almost) always kept
+        let mut hits = 0;
+        for seed in 0..1000 {
+            let mut res = WeightedReservoir::new(2, seed);
+            res.extend((0..500).map(|i| (i, if i == 250 { 1e6 } else { 1.0 })));
+            hits += res.iter().filter(|&&i| i == 250).count();
+        }
+        assert!(hits > 990);
+    }
+}
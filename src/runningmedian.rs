@@ -0,0 +1,140 @@
+//! A streaming median tracker using two heaps.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/runningmedian.rs
+//!
+//! The lower half is kept in a max heap and the upper half in a min heap,
+//! the lower half has the same or one more items than the upper half.
+//! remove() makes it work over sliding windows, but it is O(n) since
+//! Heap::remove_where has to search for the item.
+
+use crate::heap::Heap;
+use std::cmp::Reverse;
+
+#[derive(Debug)]
+pub struct RunningMedian<T: PartialOrd> {
+    low: Heap<Reverse<T>>,
+    high: Heap<T>,
+}
+
+impl<T: PartialOrd> Default for RunningMedian<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd> RunningMedian<T> {
+    pub fn new() -> Self {
+        RunningMedian {
+            low: Heap::new(),
+            high: Heap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.low.len() + self.high.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.low.is_empty()
+    }
+
+    pub fn push(&mut self, item: T) {
+        match self.low.peek() {
+            Some(Reverse(top)) if item > *top => self.high.push(item),
+            _ => self.low.push(Reverse(item)),
+        }
+        self.rebalance();
+    }
+
+    /// the lower median if the number of items is even
+    pub fn median(&self) -> Option<&T> {
+        self.low.peek().map(|Reverse(x)| x)
+    }
+
+    /// the higher median if the number of items is even
+    pub fn median_high(&self) -> Option<&T> {
+        if self.low.len() == self.high.len() {
+            self.high.peek()
+        } else {
+            self.median()
+        }
+    }
+
+    /// remove one item equal to the given one, return false if not found
+    pub fn remove(&mut self, item: &T) -> bool {
+        let found = match self.low.peek() {
+            Some(Reverse(top)) if item <= top => {
+                self.low.remove_where(|Reverse(x)| x == item).is_some()
+            }
+            _ => self.high.remove_where(|x| x == item).is_some(),
+        };
+        if found {
+            self.rebalance();
+        }
+        found
+    }
+
+    fn rebalance(&mut self) {
+        if self.low.len() > self.high.len() + 1 {
+            if let Some(Reverse(x)) = self.low.pop() {
+                self.high.push(x);
+            }
+        } else if self.high.len() > self.low.len() {
+            if let Some(x) = self.high.pop() {
+                self.low.push(Reverse(x));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_median() {
+        let mut rm = RunningMedian::new();
+        assert_eq!(None, rm.median());
+        assert!(!rm.remove(&1));
+        for (x, lo, hi) in [(5, 5, 5), (1, 1, 5), (9, 5, 5), (7, 5, 7), (3, 5, 5)] {
+            rm.push(x);
+            assert_eq!(Some(&lo), rm.median());
+            assert_eq!(Some(&hi), rm.median_high());
+        }
+        assert_eq!(5, rm.len());
+        assert!(rm.remove(&5));
+        assert_eq!((Some(&3), Some(&7)), (rm.median(), rm.median_high()));
+        assert!(!rm.remove(&5));
+        assert!(rm.remove(&1) && rm.remove(&3) && rm.remove(&9));
+        assert_eq!(Some(&7), rm.median());
+        assert!(rm.remove(&7));
+        assert!(rm.is_empty());
+    }
+
+    #[test]
+    fn test_running_median_window() {
+        let mut seed = 7u64;
+        let data: Vec<u64> = (0..500)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (seed >> 33) % 100
+            })
+            .collect();
+        let w = 16;
+        let mut rm = RunningMedian::new();
+        for (i, &x) in data.iter().enumerate() {
+            rm.push(x);
+            if i >= w {
+                assert!(rm.remove(&data[i - w]));
+            }
+            let mut window = data[i.saturating_sub(w - 1)..=i].to_vec();
+            window.sort();
+            assert_eq!(window.len(), rm.len());
+            assert_eq!(Some(&window[(window.len() - 1) / 2]), rm.median());
+            assert_eq!(Some(&window[window.len() / 2]), rm.median_high());
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! A thread-safe blocking priority work queue built on Heap.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/sharedheap.rs
+//!
+//! All methods take &self, so a SharedHeap can be shared by reference
+//! (e.g. in std::thread::scope) or by Arc.
+//! After close(), push() is refused, and pop() keeps returning the remaining
+//! items (smallest first), then None instead of blocking.
+
+use crate::heap::Heap;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Inner<T: PartialOrd> {
+    heap: Heap<T>,
+    closed: bool,
+}
+
+#[derive(Debug)]
+pub struct SharedHeap<T: PartialOrd> {
+    inner: Mutex<Inner<T>>,
+    cond: Condvar,
+}
+
+impl<T: PartialOrd> Default for SharedHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd> SharedHeap<T> {
+    pub fn new() -> Self {
+        SharedHeap {
+            inner: Mutex::new(Inner {
+                heap: Heap::new(),
+                closed: false,
+            }),
+            cond: Condvar::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().heap.is_empty()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// return the item back if the heap is closed
+    pub fn push(&self, item: T) -> Result<(), T> {
+        let mut inner = self.lock();
+        if inner.closed {
+            return Err(item);
+        }
+        inner.heap.push(item);
+        drop(inner);
+        self.cond.notify_one();
+        Ok(())
+    }
+
+    /// block until an item is available, None if closed and drained
+    pub fn pop(&self) -> Option<T> {
+        let mut inner = self.lock();
+        loop {
+            if let Some(item) = inner.heap.pop() {
+                return Some(item);
+            }
+            if inner.closed {
+                return None;
+            }
+            inner = self.cond.wait(inner).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// block at most timeout, None if timed out or closed and drained
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        let mut inner = self.lock();
+        loop {
+            if let Some(item) = inner.heap.pop() {
+                return Some(item);
+            }
+            let now = Instant::now();
+            if inner.closed || now >= deadline {
+                return None;
+            }
+            inner = self
+                .cond
+                .wait_timeout(inner, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    pub fn try_pop(&self) -> Option<T> {
+        self.lock().heap.pop()
+    }
+
+    /// refuse further pushes and wake up all the blocking pops
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.cond.notify_all();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        // a panicking user comparison can not leave the heap in an unsafe state
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_shared_heap() {
+        let heap = SharedHeap::new();
+        assert_eq!(None, heap.try_pop());
+        assert_eq!(None, heap.pop_timeout(Duration::from_millis(10)));
+        for i in [3, 1, 2] {
+            heap.push(i).unwrap();
+        }
+        assert_eq!(Some(1), heap.pop());
+        assert_eq!(Some(2), heap.try_pop());
+        heap.close();
+        assert!(heap.is_closed());
+        assert_eq!(Err(4), heap.push(4));
+        assert_eq!(1, heap.len());
+        assert_eq!(Some(3), heap.pop());
+        assert_eq!(None, heap.pop());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_shared_heap_scope() {
+        let heap = SharedHeap::new();
+        let n = 1000;
+        let sums: Vec<usize> = thread::scope(|s| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut sum = 0;
+                        while let Some(x) = heap.pop() {
+                            sum += x;
+                        }
+                        sum
+                    })
+                })
+                .collect();
+            let producers: Vec<_> = (0..2)
+                .map(|k| {
+                    let heap = &heap;
+                    s.spawn(move || {
+                        for i in (k..n).step_by(2) {
+                            heap.push(i).unwrap();
+                        }
+                    })
+                })
+                .collect();
+            for p in producers {
+                p.join().unwrap();
+            }
+            heap.close();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        assert_eq!(n * (n - 1) / 2, sums.iter().sum::<usize>());
+    }
+
+    #[test]
+    fn test_shared_heap_close_wakes_waiters() {
+        let heap = SharedHeap::<i32>::new();
+        thread::scope(|s| {
+            let waiters: Vec<_> = (0..3)
+                .map(|_| s.spawn(|| heap.pop_timeout(Duration::from_secs(60))))
+                .collect();
+            thread::sleep(Duration::from_millis(20));
+            heap.close();
+            for w in waiters {
+                assert_eq!(None, w.join().unwrap());
+            }
+        });
+    }
+}
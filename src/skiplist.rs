@@ -0,0 +1,413 @@
+//! A skip list (ordered set) with span counts, an alternative to Avl and Rbt.
+//!
+//! version 0.1.1
+//! https://github.com/wufangjie/utils/blob/main/src/skiplist.rs
+//!
+//! Nodes are kept in a Vec and linked by index (the removed slots are reused),
+//! so there is no unsafe code here. nodes[0] is the head, which has no data.
+//! Every link also records its span (how many nodes it skips over at level
+//! 0), as redis zset does, so get_by_index() is O(log n) too.
+//! A new node has level k + 1 with probability (1 - P) * P^k, P = 1/4,
+//! the levels come from a seedable Rng, so the shape is reproducible.
+
+use crate::rng::Rng;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+const MAX_LEVEL: usize = 32;
+const HEAD: usize = 0;
+const NIL: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    node: usize,
+    span: usize, // NOTE: to the end of the list (len - rank) if node is NIL
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    data: Option<T>, // None for the head and the free slots
+    next: Vec<Link>,
+}
+
+pub struct SkipList<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+    level: usize, // the number of levels in use, at least 1
+    len: usize,
+    rng: Rng,
+}
+
+impl<T: Ord> Default for SkipList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> SkipList<T> {
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// the same seed and operations make the same shape
+    pub fn with_seed(seed: u64) -> Self {
+        let head = Node {
+            data: None,
+            next: vec![Link { node: NIL, span: 0 }; MAX_LEVEL],
+        };
+        SkipList {
+            nodes: vec![head],
+            free: vec![],
+            level: 1,
+            len: 0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// the number of levels in use
+    pub fn height(&self) -> usize {
+        self.level
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        let node = self.first_not(|x| x < item);
+        node != NIL && self.data(node) == item
+    }
+
+    /// return false if item is already in the list
+    pub fn insert(&mut self, item: T) -> bool {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL]; // the rank of update[l] (head is 0)
+        let mut cur = HEAD;
+        for l in (0..self.level).rev() {
+            rank[l] = if l + 1 == self.level { 0 } else { rank[l + 1] };
+            loop {
+                let link = self.nodes[cur].next[l];
+                if link.node == NIL || *self.data(link.node) >= item {
+                    break;
+                }
+                rank[l] += link.span;
+                cur = link.node;
+            }
+            update[l] = cur;
+        }
+        let next = self.nodes[cur].next[0].node;
+        if next != NIL && *self.data(next) == item {
+            return false;
+        }
+
+        let level = self.random_level();
+        for l in self.level..level {
+            // rank[l] and update[l] are already 0 and HEAD
+            self.nodes[HEAD].next[l].span = self.len;
+        }
+        self.level = self.level.max(level);
+        let new = self.alloc(item, level);
+        for l in 0..level {
+            let link = self.nodes[update[l]].next[l];
+            let before = rank[0] - rank[l]; // the nodes between update[l] and new
+            self.nodes[new].next[l] = Link {
+                node: link.node,
+                span: link.span - before,
+            };
+            self.nodes[update[l]].next[l] = Link {
+                node: new,
+                span: before + 1,
+            };
+        }
+        for (l, &u) in update.iter().enumerate().take(self.level).skip(level) {
+            self.nodes[u].next[l].span += 1;
+        }
+        self.len += 1;
+        true
+    }
+
+    /// return false if item is not in the list
+    pub fn remove(&mut self, item: &T) -> bool {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut cur = HEAD;
+        for l in (0..self.level).rev() {
+            loop {
+                let next = self.nodes[cur].next[l].node;
+                if next == NIL || self.data(next) >= item {
+                    break;
+                }
+                cur = next;
+            }
+            update[l] = cur;
+        }
+        let node = self.nodes[cur].next[0].node;
+        if node == NIL || self.data(node) != item {
+            return false;
+        }
+
+        for (l, &u) in update.iter().enumerate().take(self.level) {
+            let skip = self.nodes[node].next.get(l).copied();
+            let link = &mut self.nodes[u].next[l];
+            match skip {
+                Some(skip) if link.node == node => {
+                    link.node = skip.node;
+                    link.span = link.span + skip.span - 1;
+                }
+                _ => link.span -= 1,
+            }
+        }
+        while self.level > 1 && self.nodes[HEAD].next[self.level - 1].node == NIL {
+            self.level -= 1;
+        }
+        self.nodes[node].data = None;
+        self.nodes[node].next.clear();
+        self.free.push(node);
+        self.len -= 1;
+        true
+    }
+
+    /// the i-th smallest one (0-based)
+    pub fn get_by_index(&self, i: usize) -> Option<&T> {
+        if i >= self.len {
+            return None;
+        }
+        let target = i + 1; // the rank of the node
+        let mut traversed = 0;
+        let mut cur = HEAD;
+        for l in (0..self.level).rev() {
+            loop {
+                let link = self.nodes[cur].next[l];
+                if link.node == NIL || traversed + link.span > target {
+                    break;
+                }
+                traversed += link.span;
+                cur = link.node;
+            }
+            if traversed == target {
+                return Some(self.data(cur));
+            }
+        }
+        unreachable!("the spans are broken")
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.range(..)
+    }
+
+    /// the ones in range, in ascending order
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Iter<'_, T> {
+        let mut cur = match range.start_bound() {
+            Bound::Included(x) => self.first_not(|y| y < x),
+            Bound::Excluded(x) => self.first_not(|y| y <= x),
+            Bound::Unbounded => self.nodes[HEAD].next[0].node,
+        };
+        let stop = match range.end_bound() {
+            Bound::Included(x) => self.first_not(|y| y <= x),
+            Bound::Excluded(x) => self.first_not(|y| y < x),
+            Bound::Unbounded => NIL,
+        };
+        // start > end (cur may be NIL while stop is not), empty
+        if cur == NIL || (stop != NIL && self.data(cur) > self.data(stop)) {
+            cur = stop;
+        }
+        Iter {
+            list: self,
+            cur,
+            stop,
+        }
+    }
+
+    /// the first node which does not satisfy before (before is monotone)
+    fn first_not(&self, before: impl Fn(&T) -> bool) -> usize {
+        let mut cur = HEAD;
+        for l in (0..self.level).rev() {
+            loop {
+                let next = self.nodes[cur].next[l].node;
+                if next == NIL || !before(self.data(next)) {
+                    break;
+                }
+                cur = next;
+            }
+        }
+        self.nodes[cur].next[0].node
+    }
+
+    fn random_level(&mut self) -> usize {
+        // every two trailing zeros is a 1/4 chance
+        (self.rng.next_u64().trailing_zeros() as usize / 2 + 1).min(MAX_LEVEL)
+    }
+
+    fn alloc(&mut self, item: T, level: usize) -> usize {
+        let node = Node {
+            data: Some(item),
+            next: vec![Link { node: NIL, span: 0 }; level],
+        };
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    #[inline]
+    fn data(&self, i: usize) -> &T {
+        self.nodes[i].data.as_ref().unwrap()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SkipList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut lst = Self::new();
+        lst.extend(iter);
+        lst
+    }
+}
+
+impl<T: Ord> Extend<T> for SkipList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for SkipList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a, T> {
+    list: &'a SkipList<T>,
+    cur: usize,
+    stop: usize, // the first node not yielded, NIL for the end
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur == self.stop {
+            return None;
+        }
+        let node = &self.list.nodes[self.cur];
+        self.cur = node.next[0].node;
+        node.data.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// the spans at every level agree with the ranks at level 0
+    fn check<T: Ord + Copy + fmt::Debug>(lst: &SkipList<T>, expected: &BTreeSet<T>) {
+        let v: Vec<T> = expected.iter().copied().collect();
+        assert_eq!(v, lst.iter().copied().collect::<Vec<_>>());
+        assert_eq!(v.len(), lst.len());
+        for l in 0..lst.height() {
+            let (mut cur, mut rank) = (HEAD, 0);
+            loop {
+                let link = lst.nodes[cur].next[l];
+                rank += link.span;
+                if link.node == NIL {
+                    assert_eq!(v.len(), rank);
+                    break;
+                }
+                assert_eq!(v[rank - 1], *lst.data(link.node));
+                cur = link.node;
+            }
+        }
+        for (i, x) in v.iter().enumerate() {
+            assert_eq!(Some(x), lst.get_by_index(i));
+        }
+        assert_eq!(None, lst.get_by_index(v.len()));
+    }
+
+    #[test]
+    fn test_skiplist() {
+        let mut lst = SkipList::new();
+        assert!(lst.is_empty());
+        assert_eq!(None, lst.get_by_index(0));
+        assert!(!lst.remove(&1));
+        for x in [5, 1, 9, 3, 7] {
+            assert!(lst.insert(x));
+        }
+        assert!(!lst.insert(3));
+        assert_eq!("{1, 3, 5, 7, 9}", format!("{lst:?}"));
+        assert!(lst.contains(&7));
+        assert!(!lst.contains(&4));
+        assert_eq!(Some(&5), lst.get_by_index(2));
+
+        let range = |r: (Bound<i32>, Bound<i32>)| lst.range(r).copied().collect::<Vec<_>>();
+        assert_eq!(vec![3, 5, 7], lst.range(2..8).copied().collect::<Vec<_>>());
+        assert_eq!(vec![3, 5, 7], lst.range(3..=7).copied().collect::<Vec<_>>());
+        assert_eq!(vec![1, 3], lst.range(..5).copied().collect::<Vec<_>>());
+        assert_eq!(vec![7, 9], lst.range(6..).copied().collect::<Vec<_>>());
+        assert_eq!(vec![5], range((Bound::Excluded(3), Bound::Excluded(7))));
+        assert!(range((Bound::Excluded(5), Bound::Excluded(5))).is_empty());
+        assert!(range((Bound::Included(8), Bound::Included(4))).is_empty());
+        assert!(range((Bound::Included(10), Bound::Unbounded)).is_empty());
+        assert!(range((Bound::Included(10), Bound::Excluded(5))).is_empty());
+        assert!(range((Bound::Excluded(9), Bound::Included(1))).is_empty());
+        assert!(SkipList::<i32>::new()
+            .range((Bound::Included(3), Bound::Excluded(1)))
+            .next()
+            .is_none());
+
+        assert!(lst.remove(&1));
+        assert!(lst.remove(&9));
+        assert!(!lst.remove(&9));
+        assert_eq!(vec![3, 5, 7], lst.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(&3), lst.get_by_index(0));
+    }
+
+    #[test]
+    fn test_skiplist_random() {
+        let mut rng = Rng::new(50);
+        let mut rand = move || rng.next_u64() as usize;
+        let mut lst = SkipList::with_seed(7);
+        let mut expected = BTreeSet::new();
+        for i in 0..3000 {
+            let x = rand() % 500;
+            if rand() % 3 == 0 {
+                assert_eq!(expected.remove(&x), lst.remove(&x));
+            } else {
+                assert_eq!(expected.insert(x), lst.insert(x));
+            }
+            if i % 100 == 0 {
+                check(&lst, &expected);
+                let (a, b) = (rand() % 500, rand() % 500);
+                let (a, b) = (a.min(b), a.max(b));
+                assert!(lst.range(a..b).eq(expected.range(a..b)));
+            }
+        }
+        check(&lst, &expected);
+        assert!(lst.nodes.len() <= 501); // the free slots are reused
+        while let Some(&x) = lst.get_by_index(0) {
+            assert!(lst.remove(&x));
+        }
+        assert_eq!(1, lst.height());
+
+        // the same seed makes the same shape
+        let shape = |seed| {
+            let mut lst = SkipList::with_seed(seed);
+            lst.extend(0..200);
+            lst.nodes.iter().map(|x| x.next.len()).collect::<Vec<_>>()
+        };
+        assert_eq!(shape(1), shape(1));
+        assert_ne!(shape(1), shape(2));
+    }
+}
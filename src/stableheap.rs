@@ -0,0 +1,169 @@
+//! A stable priority queue (FIFO among equal priorities) built on Heap.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/stableheap.rs
+//!
+//! Every pushed item records an insertion sequence number, which is used to
+//! break ties, so the smallest priority pops first and equal priorities pop
+//! in insertion order.
+
+use crate::heap::Heap;
+use std::cmp::Ordering;
+
+#[derive(Debug)]
+struct Entry<P, T> {
+    priority: P,
+    seq: u64,
+    item: T,
+}
+
+impl<P: PartialOrd, T> PartialEq for Entry<P, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<P: PartialOrd, T> PartialOrd for Entry<P, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.priority.partial_cmp(&other.priority) {
+            Some(Ordering::Equal) => Some(self.seq.cmp(&other.seq)),
+            ord => ord,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StableHeap<P: PartialOrd, T> {
+    heap: Heap<Entry<P, T>>,
+    seq: u64,
+}
+
+impl<P: PartialOrd, T> Default for StableHeap<P, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: PartialOrd, T> StableHeap<P, T> {
+    pub fn new() -> Self {
+        StableHeap {
+            heap: Heap::new(),
+            seq: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        StableHeap {
+            heap: Heap::with_capacity(capacity),
+            seq: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn push(&mut self, priority: P, item: T) {
+        let entry = self.new_entry(priority, item);
+        self.heap.push(entry);
+    }
+
+    pub fn pop(&mut self) -> Option<(P, T)> {
+        self.heap.pop().map(|e| (e.priority, e.item))
+    }
+
+    /// push then pop
+    pub fn pushpop(&mut self, priority: P, item: T) -> (P, T) {
+        let entry = self.new_entry(priority, item);
+        let e = self.heap.pushpop(entry);
+        (e.priority, e.item)
+    }
+
+    pub fn peek(&self) -> Option<(&P, &T)> {
+        self.heap.peek().map(|e| (&e.priority, &e.item))
+    }
+
+    fn new_entry(&mut self, priority: P, item: T) -> Entry<P, T> {
+        self.seq += 1;
+        Entry {
+            priority,
+            seq: self.seq,
+            item,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_heap() {
+        let mut heap = StableHeap::new();
+        assert_eq!(None, heap.pop());
+        for (p, c) in [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (3, 'e'), (2, 'f')] {
+            heap.push(p, c);
+        }
+        assert_eq!(Some((&1, &'b')), heap.peek());
+        assert_eq!((1, 'b'), heap.pushpop(1, 'g'));
+        assert_eq!((0, 'h'), heap.pushpop(0, 'h'));
+        let order: Vec<char> = std::iter::from_fn(|| heap.pop()).map(|(_, c)| c).collect();
+        assert_eq!(vec!['d', 'g', 'a', 'c', 'f', 'e'], order);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_stable_heap_random() {
+        let mut seed = 2022u64;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) as usize
+        };
+        let mut heap = StableHeap::with_capacity(1000);
+        let mut expected = vec![];
+        let mut popped = vec![];
+        for i in 0..100000 {
+            if rand() % 3 == 0 {
+                if let Some(x) = heap.pop() {
+                    popped.push(x);
+                }
+            } else {
+                let p = rand() % 10;
+                heap.push(p, i);
+                expected.push((p, i));
+            }
+        }
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+        assert_eq!(expected.len(), popped.len());
+
+        // items popped with the same priority must keep the insertion order
+        let mut last = [None; 10];
+        for &(p, i) in &popped {
+            if let Some(j) = last[p] {
+                assert!(j < i);
+            }
+            last[p] = Some(i);
+        }
+
+        // without interleaved pops, the output is just a stable sort
+        let mut heap = StableHeap::new();
+        let input: Vec<(usize, usize)> = (0..50000).map(|i| (rand() % 100, i)).collect();
+        for &(p, i) in &input {
+            heap.push(p, i);
+        }
+        let mut sorted = input.clone();
+        sorted.sort_by_key(|&(p, _)| p);
+        assert_eq!(
+            sorted,
+            std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! A bounded top-k collector built on Heap::pushpop.
+//!
+//! version 0.1.0
+//! https://github.com/wufangjie/utils/blob/main/src/topk.rs
+//!
+//! struct TopK (use new(k), offer(item), into_sorted_vec() to collect)
+//! trait TopKExt (use iter.top_k(k) or iter.bottom_k(k))
+
+use crate::heap::Heap;
+use std::cmp::Reverse;
+
+#[derive(Debug)]
+pub struct TopK<T: PartialOrd> {
+    heap: Heap<T>, // min heap, the root is the k-th best
+    k: usize,
+}
+
+impl<T: PartialOrd> TopK<T> {
+    pub fn new(k: usize) -> Self {
+        TopK {
+            heap: Heap::with_capacity(k),
+            k,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// return the item which is not (or no longer) in the top k
+    pub fn offer(&mut self, item: T) -> Option<T> {
+        if self.heap.len() < self.k {
+            self.heap.push(item);
+            None
+        } else {
+            Some(self.heap.pushpop(item))
+        }
+    }
+
+    /// the current k-th best, None if less than k items were offered
+    pub fn threshold(&self) -> Option<&T> {
+        if self.k > 0 && self.heap.len() == self.k {
+            self.heap.peek()
+        } else {
+            None
+        }
+    }
+
+    /// best first
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut ret = self.heap.into_sorted_vec();
+        ret.reverse();
+        ret
+    }
+}
+
+pub trait TopKExt<I: Iterator> {
+    /// the k largest items, in descending order
+    fn top_k(self, k: usize) -> std::vec::IntoIter<I::Item>;
+    /// the k smallest items, in ascending order
+    fn bottom_k(self, k: usize) -> std::vec::IntoIter<I::Item>;
+}
+
+impl<I> TopKExt<I> for I
+where
+    I: Iterator,
+    I::Item: PartialOrd,
+{
+    fn top_k(self, k: usize) -> std::vec::IntoIter<I::Item> {
+        let mut top = TopK::new(k);
+        for item in self {
+            top.offer(item);
+        }
+        top.into_sorted_vec().into_iter()
+    }
+
+    fn bottom_k(self, k: usize) -> std::vec::IntoIter<I::Item> {
+        let mut top = TopK::new(k);
+        for item in self {
+            top.offer(Reverse(item));
+        }
+        top.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topk() {
+        let mut top = TopK::new(3);
+        assert_eq!(None, top.threshold());
+        for i in [4, 9, 7, 3] {
+            top.offer(i);
+        }
+        assert_eq!(Some(&4), top.threshold());
+        assert_eq!(Some(1), top.offer(1));
+        assert_eq!(Some(4), top.offer(8));
+        assert_eq!(Some(&7), top.threshold());
+        assert_eq!(3, top.len());
+        assert_eq!(vec![9, 8, 7], top.into_sorted_vec());
+
+        let mut top = TopK::new(0);
+        assert_eq!(Some(42), top.offer(42));
+        assert_eq!(None, top.threshold());
+        assert!(top.is_empty());
+    }
+
+    #[test]
+    fn test_topk_ext() {
+        let lst = vec![4, 9, 7, 3, 1, 8, 6, 0, 5, 2, 0];
+        assert_eq!(
+            vec![9, 8, 7, 6],
+            lst.iter().top_k(4).copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 0, 1],
+            lst.iter().bottom_k(3).copied().collect::<Vec<_>>()
+        );
+        assert_eq!(11, lst.iter().top_k(20).len());
+        assert_eq!(0, lst.into_iter().bottom_k(0).len());
+    }
+}